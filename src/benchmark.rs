@@ -1,89 +1,365 @@
-use crate::common::{Word, MatchInfo};
-use crate::dataloader::{DataLoader, CharTranslator};
-use crate::entropy::find_best_splitter;
+use crate::common::{Word, Pattern, MatchInfo};
+use crate::dataloader::{DataLoader, CharTranslator, Language, ListFiles, LoadError, Manifest, Priors};
+use crate::entropy::{choose_guess, expected_entropy, rank_words, uncertainty, Metric, Strategy};
+use crate::model::GuessModel;
+use crate::report::{BenchmarkReport, RoundReport};
 
-use indicatif::ProgressIterator;
+use std::sync::{OnceLock, RwLock};
+
+use indicatif::ParallelProgressIterator;
 use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+
+// Candidate sets up to this size are listed in full in the outliers report
+const MAX_LISTED_CANDIDATES: usize = 12;
 
 pub struct Benchmark {
+    lang: String,
     guesses: Vec<Word>,
     solutions: Vec<Word>,
-    initial_word: Word,
-    translator: CharTranslator
+    // The opening word, which is only chosen when it's first needed,
+    // since it takes as long as ranking all the guesses
+    initial_word: OnceLock<Word>,
+    translator: CharTranslator,
+    manifest: Manifest,
+    priors: Option<Priors>,
+    files: ListFiles,
+    hard_mode: bool,
+    strategy: Strategy,
+    // Best guesses found so far for every sequence of (guess, pattern)
+    // observations, shared by all the rounds that are played in parallel.
+    // Since the strategy is deterministic, many answers go through the
+    // same observations, especially during the first few turns.
+    cache: RwLock<FxHashMap<Vec<(Word, Pattern)>, Word>>,
+}
+
+// The full trace of a benchmark round for a given solution
+pub struct RoundResult {
+    pub solution: Word,
+    pub steps: Vec<Step>,
+    pub max_guesses: usize,
+}
+
+// A single guess within a round, along with the pattern that it
+// produced and the candidates that were left after applying it
+pub struct Step {
+    pub guess: Word,
+    pub pattern: Pattern,
+    pub candidates: Vec<Word>,
+}
+
+// A group of solutions that produce the same pattern with the
+// opening word, along with the best second guess for them
+pub struct Bucket {
+    pub pattern: Pattern,
+    pub solutions: Vec<Word>,
+    pub guess: Word,
+    pub entropy: f64,
 }
 
 impl Benchmark {
-    pub fn init(lang: &str) -> Self {
-        let (guesses, solutions, translator) = DataLoader::load_language(lang);
+    pub fn init(loader: &DataLoader, lang: &str, hard_mode: bool, strategy: Strategy) -> Result<Self, LoadError> {
+        let Language { guesses, solutions, translator, manifest, priors, files } = loader.load_language(lang)?;
 
-        // The best initial word is always the same for a given
-        // language, so we compute it during initialization
-        let initial_word = find_best_splitter(&guesses, &solutions);
-        Self { guesses, solutions, initial_word, translator }
+        let initial_word = OnceLock::new();
+        let cache = RwLock::new(FxHashMap::default());
+        Ok(Self { lang: lang.to_lowercase(), guesses, solutions, initial_word, translator, manifest, priors, files, hard_mode, strategy, cache })
     }
 
-    pub fn run(&self) {
+    pub fn run(&self, n_outliers: usize) -> BenchmarkReport {
+        println!("Guesses: {} ({} words)", self.files.guesses.join(", "), self.guesses.len());
+        println!("Solutions: {} ({} words)", self.files.solutions.join(", "), self.solutions.len());
+        if let Some(day) = self.files.used_before {
+            println!("Left out the answers used before {}", day);
+        }
         println!("Running benchmark...");
-        let mut counts = [0; 7];
+        let results = self.play_all();
+        let counts = distribution(&results, self.manifest.max_guesses);
+
+        println!("Opening word: {}", self.opener().as_string(&self.translator));
+        let avg = print_distribution(&counts);
 
-        for solution in self.solutions.iter().progress() {
-            let tries = self.play_round(*solution);
-            counts[tries - 1] += 1;
+        // With word frequencies, the answers aren't equally likely, so
+        // the average that is expected when playing is the weighted one
+        if let Some(priors) = &self.priors {
+            let (total, weights) = results.iter()
+                .map(|r| (r.tries() as f64 * priors.weight(&r.solution), priors.weight(&r.solution)))
+                .fold((0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+            println!("Weighted average: {:.4}", total / weights);
         }
+        // The model is fitted to the rounds that were just played, so that
+        // it can be used to score the guesses in later runs
+        let model = self.fit_model(&results);
+        println!("Guess model: {}", model.describe());
+        self.print_outliers(&results, n_outliers);
 
-        let n_runs = self.solutions.len() as f32;
-        let mut avg = 0.0;
+        BenchmarkReport {
+            language: self.lang.clone(),
+            opener: self.opener().as_string(&self.translator),
+            average: avg as f64,
+            distribution: counts,
+            rounds: results.iter().map(|r| self.round_report(r)).collect(),
+            model: Some(model),
+            files: self.files.clone(),
+        }
+    }
 
-        println!("Opening word: {}", self.initial_word.as_string(&self.translator));
-        
-        for (i, &count) in counts.iter().enumerate() {
-            let s = if i == 6 { "X".to_owned() } else { (i+1).to_string() };
-            let ratio = count as f32 / n_runs;
-            avg += (i+1) as f32 * ratio;
-            println!("- {}: {} ({:.2}%)", s, count, ratio * 100.0);
+    // Plays a round for every solution, showing a progress bar
+    pub fn play_all(&self) -> Vec<RoundResult> {
+        // The rounds are played in parallel, collecting them keeps
+        // the results in the same order as the solutions list. The
+        // opener is chosen before, so that the rounds don't wait for it.
+        let opener = self.opener();
+        self.solutions.par_iter()
+            .progress_count(self.solutions.len() as u64)
+            .map(|solution| self.play_round(*solution, opener))
+            .collect()
+    }
+
+    // Ranks the possible opening words according to a metric
+    pub fn rank_openers(&self, metric: Metric, n: usize) -> Vec<(Word, f64)> {
+        rank_words(&self.guesses, &self.solutions, self.priors.as_ref(), self.manifest.duplicates, metric, n)
+    }
+
+    // Changes the opening word for the next runs. The cached guesses
+    // all start with the previous opener, so they are discarded.
+    pub fn set_opener(&mut self, word: Word) {
+        self.initial_word = OnceLock::from(word);
+        self.cache.write().unwrap().clear();
+    }
+
+    pub fn translator(&self) -> &CharTranslator {
+        &self.translator
+    }
+
+    // The best initial word is always the same for a given language,
+    // so it's computed once
+    pub fn opener(&self) -> Word {
+        *self.initial_word.get_or_init(|| {
+            choose_guess(&self.guesses, &self.solutions, self.priors.as_ref(), self.manifest.duplicates, self.strategy)
+        })
+    }
+
+    // Parses a word provided by the user, as long as it's a valid guess
+    pub fn parse_guess(&self, word: &str) -> Option<Word> {
+        let word = self.translator.normalize(word);
+        let tiles = self.translator.tiles(&word);
+        if tiles.len() != self.manifest.word_length || !tiles.iter().all(|tile| self.translator.has_char(tile)) {
+            return None;
         }
 
-        println!("Average: {:.4}", avg);
+        let word = Word::from_str(&word, &self.translator);
+        self.guesses.contains(&word).then_some(word)
+    }
+
+    // Groups the solutions by the pattern that they produce with the
+    // opening word, and finds the second guess that the benchmark would
+    // use for each group. The groups are sorted from largest to smallest.
+    pub fn opener_buckets(&self) -> Vec<Bucket> {
+        let opener = self.opener();
+        let n_chars = self.translator.count();
+        let mut groups: FxHashMap<Pattern, Vec<Word>> = FxHashMap::default();
+        for sol in &self.solutions {
+            groups.entry(opener.compute_pattern(sol, self.manifest.duplicates)).or_default().push(*sol);
+        }
+
+        let mut buckets: Vec<Bucket> = groups.into_par_iter().map(|(pattern, solutions)| {
+            let match_data = MatchInfo::from_word_match(&opener, &pattern, n_chars, self.manifest.duplicates);
+            let hard_guesses: Vec<Word>;
+            let guesses = if self.hard_mode {
+                hard_guesses = self.guesses.iter().copied().filter(|w| match_data.allows_in_hard_mode(w)).collect();
+                &hard_guesses
+            } else {
+                &self.guesses
+            };
+            let guess = self.best_guess_after(&[(opener, pattern)], guesses, &solutions);
+
+            let entropy = expected_entropy(&guess, &solutions, self.priors.as_ref(), self.manifest.duplicates);
+            Bucket { pattern, solutions, guess, entropy }
+        }).collect();
+
+        // Break ties by pattern so that the order is always the same
+        buckets.sort_by_key(|b| (std::cmp::Reverse(b.solutions.len()), b.pattern.to_index()));
+        buckets
     }
 
-    // Plays one round for a given solution and returns the amount
-    // of tries it took to get to the solution
-    fn play_round(&self, solution: Word) -> usize {
-        let mut attempts = 1;
+    // Plays one round for a given solution and returns the trace
+    // of all the guesses that it took to get to the solution
+    fn play_round(&self, solution: Word, opener: Word) -> RoundResult {
+        let max_guesses = self.manifest.max_guesses;
+        let mut steps = Vec::with_capacity(max_guesses);
+        let mut history = Vec::with_capacity(max_guesses);
         let mut solutions = self.solutions.clone();
         let n_chars = self.translator.count();
 
-        while attempts <= 6 {
+        // In hard mode, the words that can be guessed are restricted
+        // by every pattern, so we keep track of them too
+        let mut hard_guesses = if self.hard_mode { Some(self.guesses.clone()) } else { None };
+
+        while steps.len() < max_guesses {
             // Determine the word that we are going to try
-            let guess = if solutions.len() <= 2 {
-                // If there is only one or two possible solutions left
-                // we use the first one
-                // In the case of two solutions, the worst case is
-                // already two turns, so by using one of them,
-                // we'll get it right in one turn 50% of the time.
+            let guess = if solutions.len() == 1 {
+                // If there is only one possible solution left, every
+                // strategy would use it
                 solutions[0]
-            } else if attempts == 1 {
+            } else if steps.is_empty() {
                 // If it's the first attempt, use the initial word
-                self.initial_word
+                opener
             } else {
                 // Otherwise, determine the optimal word for the remaining
-                // set of answers, updating it in the process
-                find_best_splitter(&self.guesses, &solutions)
+                // set of answers, unless another round already did
+                let guesses = hard_guesses.as_deref().unwrap_or(&self.guesses);
+                self.best_guess_after(&history, guesses, &solutions)
             };
 
+            // Get the comparison pattern with the solution
+            // and update the solutions list
+            let pattern = guess.compute_pattern(&solution, self.manifest.duplicates);
+            let match_data = MatchInfo::from_word_match(&guess, &pattern, n_chars, self.manifest.duplicates);
+            solutions.retain(|w| match_data.matches(w));
+            if let Some(guesses) = &mut hard_guesses {
+                guesses.retain(|w| match_data.allows_in_hard_mode(w));
+            }
+            history.push((guess, pattern));
+            steps.push(Step { guess, pattern, candidates: solutions.clone() });
+
             // If the guess is the solution, the game has finished
             if guess == solution {
                 break;
             }
+        }
 
-            // Otherwise, get the comparison pattern with the solution
-            // and update the solutions list
-            let pattern = guess.compute_pattern(&solution);
-            let match_data = MatchInfo::from_word_match(&guess, &pattern, n_chars);
-            solutions = solutions.into_par_iter().filter(|w| match_data.matches(w)).collect();
-            attempts += 1;
+        RoundResult { solution, steps, max_guesses }
+    }
+
+    // Finds the best guess for the candidates left after a sequence
+    // of observations, looking it up in the shared cache first
+    fn best_guess_after(&self, history: &[(Word, Pattern)], guesses: &[Word], solutions: &[Word]) -> Word {
+        if let Some(&guess) = self.cache.read().unwrap().get(history) {
+            return guess;
+        }
+
+        // Two rounds may end up computing the same guess at the same
+        // time, which is harmless since they will both get the same word
+        let guess = choose_guess(guesses, solutions, self.priors.as_ref(), self.manifest.duplicates, self.strategy);
+        self.cache.write().unwrap().insert(history.to_vec(), guess);
+        guess
+    }
+
+    // Fits the model of how many guesses are needed for some amount of
+    // uncertainty to the rounds that were played, using the uncertainty
+    // before every turn and the guesses that it took from then on
+    fn fit_model(&self, results: &[RoundResult]) -> GuessModel {
+        let priors = self.priors.as_ref();
+        let initial_bits = uncertainty(&self.solutions, priors);
+        let points: Vec<(f64, f64)> = results.par_iter()
+            .flat_map_iter(|result| (0..result.steps.len()).map(move |i| {
+                let bits = if i == 0 { initial_bits } else { uncertainty(&result.steps[i - 1].candidates, priors) };
+                (bits, (result.tries() - i) as f64)
+            }))
+            .collect();
+        GuessModel::fit(&points)
+    }
+
+    // Lists every answer that could not be solved, and the slowest
+    // solves among the rest, along with the trace of their guesses
+    fn print_outliers(&self, results: &[RoundResult], n_outliers: usize) {
+        let failed: Vec<&RoundResult> = results.iter().filter(|r| !r.is_solved()).collect();
+        if !failed.is_empty() {
+            println!();
+            println!("Failed answers ({}):", failed.len());
+            failed.iter().for_each(|r| self.print_round(r));
+        }
+
+        // The sort is stable, so ties keep the order of the solutions list
+        let mut slowest: Vec<&RoundResult> = results.iter().filter(|r| r.is_solved()).collect();
+        slowest.sort_by_key(|r| std::cmp::Reverse(r.tries()));
+        slowest.truncate(n_outliers);
+
+        if !slowest.is_empty() {
+            println!();
+            println!("Slowest solves ({}):", slowest.len());
+            slowest.iter().for_each(|r| self.print_round(r));
+        }
+    }
+
+    fn round_report(&self, result: &RoundResult) -> RoundReport {
+        RoundReport {
+            answer: result.solution.as_string(&self.translator),
+            tries: result.tries(),
+            solved: result.is_solved(),
+            guesses: result.steps.iter().map(|s| s.guess.as_string(&self.translator)).collect(),
+            patterns: result.steps.iter().map(|s| s.pattern.as_string()).collect(),
+        }
+    }
+
+    fn print_round(&self, result: &RoundResult) {
+        let tries = if result.is_solved() { result.tries().to_string() } else { "X".to_owned() };
+        println!("- {} ({})", result.solution.as_string(&self.translator), tries);
+
+        for step in &result.steps {
+            let n_left = step.candidates.len();
+            print!("    {} {} {:>5} left", step.guess.as_string(&self.translator), step.pattern.as_string(), n_left);
+
+            // Only list the candidates when there are few enough of them
+            // to be readable, which is usually when a family of similar
+            // words (e.g. _ATCH) is slowing things down
+            if n_left > 1 && n_left <= MAX_LISTED_CANDIDATES {
+                let words: Vec<String> = step.candidates.iter()
+                    .map(|w| w.as_string(&self.translator))
+                    .collect();
+                print!(": {}", words.join(", "));
+            }
+
+            println!();
         }
+    }
+}
+
+// Counts how many rounds were solved in every number of tries,
+// with the last position corresponding to the failed ones
+pub fn distribution(results: &[RoundResult], max_guesses: usize) -> Vec<usize> {
+    let mut counts = vec![0; max_guesses + 1];
+    for result in results {
+        counts[result.tries() - 1] += 1;
+    }
+    counts
+}
+
+// Prints how many rounds took every number of tries, the last count
+// being the failures, followed by the average number of tries, which
+// is returned
+pub fn print_distribution(counts: &[usize]) -> f32 {
+    let n_runs = counts.iter().sum::<usize>() as f32;
+    let max_guesses = counts.len() - 1;
+    let mut avg = 0.0;
 
-        attempts
+    for (i, &count) in counts.iter().enumerate() {
+        let s = if i == max_guesses { "X".to_owned() } else { (i+1).to_string() };
+        let ratio = count as f32 / n_runs;
+        avg += (i+1) as f32 * ratio;
+        println!("- {}: {} ({:.2}%)", s, count, ratio * 100.0);
     }
-}
\ No newline at end of file
+
+    println!("Average: {:.4}", avg);
+    avg
+}
+
+// Computes the average number of tries, counting failures as one
+// more than the maximum number of guesses
+pub fn average(results: &[RoundResult]) -> f64 {
+    let total: usize = results.iter().map(|r| r.tries()).sum();
+    total as f64 / results.len() as f64
+}
+
+impl RoundResult {
+    pub fn is_solved(&self) -> bool {
+        self.steps.last().is_some_and(|step| step.pattern.is_solved())
+    }
+
+    // The number of tries that it took to solve the word, or one more
+    // than the maximum if it could not be solved within the allowed attempts
+    pub fn tries(&self) -> usize {
+        if self.is_solved() { self.steps.len() } else { self.max_guesses + 1 }
+    }
+}
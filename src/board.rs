@@ -0,0 +1,400 @@
+use crate::common::{Colors, Pattern, Word};
+use crate::entropy::{best_by_entropy, uncertainty};
+use crate::input::{print_outcome, print_turn, read_event, word_key, yes_no_key, PatternEntry, PatternInput};
+use crate::keys::{EntryMode, KeyBindings};
+use crate::play::{Game, GameUi, Outcome};
+
+use std::io::{stdout, Stdout, Write};
+use std::panic;
+
+use crossterm::{cursor, execute, queue, terminal};
+use crossterm::event::{Event, KeyEvent};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use unicode_normalization::char::is_combining_mark;
+
+// Avoid clashes with crossterm::style::Color
+type WordleColor = crate::common::Color;
+
+// How many of the best guesses and the remaining candidates are listed
+const N_SUGGESTIONS: usize = 5;
+const N_CANDIDATES: usize = 30;
+
+// The rows of the keyboard for languages that only use these letters
+const QWERTY: [&str; 3] = ["QWERTYUIOP", "ASDFGHJKL", "ZXCVBNM"];
+
+// Tiles that don't have a color yet
+const EMPTY: Color = Color::AnsiValue(237);
+// The tile whose color is entered next
+const NEXT: Color = Color::AnsiValue(250);
+// Keys of letters that are known not to be in the answer
+const ABSENT: Color = Color::AnsiValue(238);
+
+// Plays the game on a full-screen board, with a keyboard that shows
+// what is known about every letter, and a panel with the candidates
+// that remain and the best guesses
+pub struct BoardUi {
+    title: String,
+    keys: KeyBindings,
+    // The word that is being asked for, and the pattern entered so far
+    current: Option<(Word, PatternEntry)>,
+    // The best guesses for the current turn, along with the information
+    // that they are expected to gain
+    suggestions: Vec<(Word, f64)>,
+    // A question, or how the game ended
+    status: String,
+    // A problem that was reported, which is shown until a key is pressed
+    warning: Option<String>,
+    screen: Option<Screen>,
+}
+
+// Keeps the terminal in full-screen raw mode while it's alive
+struct Screen;
+
+impl Screen {
+    fn enter() -> Self {
+        terminal::enable_raw_mode()
+            .expect("Your console is not compatible with raw mode, which is required to run Eldrow.");
+        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide).unwrap();
+
+        // Restore the terminal before a panic prints its message, which
+        // would be lost in the alternate screen otherwise
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            hook(info);
+        }));
+        Screen
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn restore_terminal() {
+    let _ = execute!(stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+    let _ = terminal::disable_raw_mode();
+}
+
+impl BoardUi {
+    pub fn new(keys: KeyBindings) -> Self {
+        Self { title: String::new(), keys, current: None, suggestions: vec![], status: String::new(), warning: None, screen: None }
+    }
+
+    // Waits for a key press, redrawing the board if the terminal is resized
+    fn wait_for_key<T>(&self, game: &Game, mut handle_key: impl FnMut(&KeyEvent) -> Option<T>) -> T {
+        loop {
+            match read_event() {
+                Event::Key(ev) => if let Some(result) = handle_key(&ev) {
+                    return result;
+                },
+                Event::Resize(..) => self.draw(game),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&self, game: &Game) {
+        let mut out = stdout();
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        queue!(out, ResetColor, terminal::Clear(terminal::ClearType::All)).unwrap();
+
+        // The last lines are kept for the help, which is wrapped at the
+        // separators of the keys, the warning and the status
+        let mut bottom: Vec<String> = vec![];
+        for part in self.keys.help().split(", ") {
+            match bottom.last_mut() {
+                Some(text) if text.chars().count() + 2 + part.chars().count() <= width.saturating_sub(2) as usize => {
+                    text.push_str(", ");
+                    text.push_str(part);
+                },
+                _ => bottom.push(part.to_owned()),
+            }
+        }
+        bottom.extend(self.warning.iter().map(|warning| format!("Warning: {}", warning)));
+        bottom.push(self.status.clone());
+        let reserved = bottom.len() as u16;
+
+        let mut y = 0;
+        let line = |out: &mut Stdout, x: u16, y: &mut u16, text: &str| {
+            if *y + reserved < height {
+                let text: String = text.chars().take(width.saturating_sub(x) as usize).collect();
+                queue!(out, cursor::MoveTo(x, *y), Print(text)).unwrap();
+            }
+            *y += 1;
+        };
+
+        queue!(out, SetAttribute(Attribute::Bold)).unwrap();
+        line(&mut out, 2, &mut y, &self.title);
+        queue!(out, SetAttribute(Attribute::Reset)).unwrap();
+        if let Some(url) = &game.manifest.url {
+            line(&mut out, 2, &mut y, url);
+        }
+        y += 1;
+
+        // The board, with a row for every guess that can be made
+        let top = y;
+        let mut board_width = 0;
+        for i in 0..game.manifest.max_guesses {
+            let mut cursor = None;
+            let tiles: Vec<(String, Color, Color)> = match (game.turns.get(i), &self.current) {
+                (Some((guess, pattern)), _) => colored_tiles(game, guess, pattern, pattern.len),
+                (None, Some((guess, entry))) if i == game.turns.len() => {
+                    let mut tiles = colored_tiles(game, guess, &entry.pattern, entry.colored());
+                    match entry.mode {
+                        EntryMode::Type => if let Some(tile) = tiles.get_mut(entry.pos) {
+                            tile.1 = NEXT;
+                            tile.2 = Color::Black;
+                        },
+                        EntryMode::Cycle => cursor = Some(entry.pos),
+                    }
+                    tiles
+                },
+                _ => vec![(" ".to_owned(), EMPTY, Color::White); game.manifest.word_length],
+            };
+            if y + reserved < height {
+                board_width = board_width.max(draw_cells(&mut out, 2, y, &tiles, cursor));
+            }
+            y += 1;
+        }
+        y += 1;
+
+        // The keyboard, with the colors of the letters
+        let status = letter_status(game);
+        for (i, row) in keyboard_rows(game).iter().enumerate() {
+            let keys: Vec<(String, Color, Color)> = row.iter()
+                .map(|&idx| {
+                    let key = game.translator.uppercase(game.translator.index_to_char(idx));
+                    match status[idx as usize] {
+                        None => (key, NEXT, Color::Black),
+                        Some(Colors::GRAY) => (key, ABSENT, Color::AnsiValue(245)),
+                        Some(color) => (key, tile_color(color), Color::Black),
+                    }
+                })
+                .collect();
+            if y + reserved < height {
+                board_width = board_width.max(draw_cells(&mut out, 2 + i as u16 * 2, y, &keys, None) + i as u16 * 2);
+            }
+            y += 1;
+        }
+        y += 1;
+
+        // The panel goes next to the board if there is room for it, or
+        // below it otherwise
+        let (x, mut y) = if width >= board_width + 2 + 4 + 30 { (board_width + 6, top) } else { (2, y) };
+        let panel_width = width.saturating_sub(x + 1) as usize;
+        let left = if game.solutions.is_empty() { 0.0 } else { uncertainty(&game.solutions, game.priors.as_ref()) };
+        line(&mut out, x, &mut y, &format!("Candidates: {} ({:.2} bits left)", game.solutions.len(), left));
+        let candidates: Vec<String> = game.solutions.iter().take(N_CANDIDATES).map(|w| w.as_string(&game.translator)).collect();
+        let more = game.solutions.len().saturating_sub(N_CANDIDATES);
+        let mut text = candidates.join(" ");
+        if more > 0 {
+            text.push_str(&format!(" and {} more", more));
+        }
+        for wrapped in wrap(&text, panel_width) {
+            line(&mut out, x, &mut y, &wrapped);
+        }
+        y += 1;
+
+        if let Some((guess, _)) = &self.current {
+            line(&mut out, x, &mut y, "Best guesses by information:");
+            for (i, (word, bits)) in self.suggestions.iter().enumerate() {
+                let marker = if word == guess { '>' } else { ' ' };
+                line(&mut out, x, &mut y, &format!("{} {}. {} {:.2} bits", marker, i + 1, word.as_string(&game.translator), bits));
+            }
+            if !self.suggestions.iter().any(|(word, _)| word == guess) {
+                line(&mut out, x, &mut y, &format!("> Guess: {}", guess.as_string(&game.translator)));
+            }
+            y += 1;
+        }
+
+        if !game.turns.is_empty() {
+            line(&mut out, x, &mut y, "Information per turn:");
+            for (i, ((guess, _), bits)) in game.turns.iter().zip(&game.bits).enumerate() {
+                line(&mut out, x, &mut y, &format!("  {}. {} {:.2} bits", i + 1, guess.as_string(&game.translator), bits));
+            }
+        }
+
+        for (i, text) in bottom.iter().enumerate() {
+            let text: String = text.chars().take(width.saturating_sub(2) as usize).collect();
+            queue!(out, cursor::MoveTo(2, height.saturating_sub(reserved) + i as u16), Print(text)).unwrap();
+        }
+        out.flush().unwrap();
+    }
+}
+
+impl GameUi for BoardUi {
+    fn start(&mut self, game: &Game, title: &str) {
+        self.title = title.to_owned();
+        self.screen = Some(Screen::enter());
+        self.draw(game);
+    }
+
+    fn show_turn(&mut self, game: &Game, _guess: &Word, _pattern: &Pattern) {
+        self.draw(game);
+    }
+
+    fn thinking(&mut self, game: &Game) {
+        self.current = None;
+        self.status = "Thinking...".to_owned();
+        self.draw(game);
+    }
+
+    fn ask_for_pattern(&mut self, game: &Game, guess: &Word, entropies: &[(Word, f64)]) -> PatternInput {
+        self.suggestions = best_by_entropy(entropies, N_SUGGESTIONS);
+        let mut entry = PatternEntry::new(guess.len, self.keys.entry);
+        self.status.clear();
+        self.current = Some((*guess, entry));
+        self.draw(game);
+
+        // The board is redrawn after every key, with the pattern so far
+        loop {
+            if let Some(input) = self.wait_for_key(game, |ev| Some(entry.handle_key(ev, &self.keys))) {
+                self.current = None;
+                return input;
+            }
+            self.current = Some((*guess, entry));
+            self.status.clear();
+            self.warning = None;
+            self.draw(game);
+        }
+    }
+
+    fn ask_for_word(&mut self, game: &Game) -> Option<String> {
+        let mut word = String::new();
+        loop {
+            self.status = format!("Word played: {}_ (enter: done, esc: cancel)", word);
+            self.draw(game);
+            if let Some(word) = self.wait_for_key(game, |ev| Some(word_key(&mut word, ev))) {
+                self.status.clear();
+                return word;
+            }
+        }
+    }
+
+    fn ask_yes_no(&mut self, game: &Game, question: &str) -> bool {
+        self.status = format!("{} [Y/n]", question);
+        self.draw(game);
+        self.wait_for_key(game, yes_no_key)
+    }
+
+    fn warn(&mut self, game: &Game, message: &str) {
+        self.warning = Some(message.to_owned());
+        self.draw(game);
+    }
+
+    fn finish(&mut self, game: &Game, outcome: &Outcome) {
+        self.current = None;
+        self.status = match outcome {
+            Outcome::Solved => format!("Solved in {} guesses!", game.turns.len()),
+            Outcome::Answer(answer) => format!("The answer is {}!", answer.as_string(&game.translator)),
+            Outcome::OutOfGuesses => "Out of guesses!".to_owned(),
+            Outcome::NoSolutions => "Oops, no solutions found...".to_owned(),
+        };
+        self.status.push_str(" Press any key to exit.");
+        self.draw(game);
+        self.wait_for_key(game, |_| Some(()));
+
+        // Leave the game on the terminal once the board is gone
+        self.screen = None;
+        for (guess, pattern) in &game.turns {
+            print_turn(game, guess, pattern);
+        }
+        print_outcome(game, outcome);
+    }
+}
+
+// The tiles of a word, with the colors of the first `pos` of them
+fn colored_tiles(game: &Game, word: &Word, pattern: &Pattern, pos: usize) -> Vec<(String, Color, Color)> {
+    word.tiles(&game.translator).into_iter().enumerate()
+        .map(|(i, tile)| if i < pos { (tile, tile_color(pattern.colors[i]), Color::Black) } else { (tile, EMPTY, Color::White) })
+        .collect()
+}
+
+// Draws a row of tiles or keys, with a space between them, returning
+// the width of the row. The cell under the cursor, if any, is drawn
+// between brackets.
+fn draw_cells(out: &mut Stdout, x: u16, y: u16, cells: &[(String, Color, Color)], cursor: Option<usize>) -> u16 {
+    queue!(out, cursor::MoveTo(x, y)).unwrap();
+    let mut row_width = 0;
+    for (i, (text, bg, fg)) in cells.iter().enumerate() {
+        let text = if cursor == Some(i) { format!("[{}]", text) } else { format!(" {} ", text) };
+        queue!(out, SetBackgroundColor(*bg), SetForegroundColor(*fg), SetAttribute(Attribute::Bold)).unwrap();
+        queue!(out, Print(&text), SetAttribute(Attribute::Reset), ResetColor, Print(" ")).unwrap();
+        row_width += display_width(&text) + 1;
+    }
+    row_width as u16
+}
+
+// What is known about every letter from the turns so far, which is
+// the best color that it got in any of them
+fn letter_status(game: &Game) -> Vec<Option<WordleColor>> {
+    let mut status = vec![None; game.translator.count()];
+    for (guess, pattern) in &game.turns {
+        for (i, &idx) in guess.letters().iter().enumerate() {
+            status[idx as usize] = status[idx as usize].max(Some(pattern.colors[i]));
+        }
+    }
+    status
+}
+
+// The letters of the language, arranged like a QWERTY keyboard if they
+// fit in one, or in rows following the alphabet otherwise
+fn keyboard_rows(game: &Game) -> Vec<Vec<u16>> {
+    let translator = &game.translator;
+    let mut letters: Vec<(u16, String)> = (0..translator.count() as u16)
+        .map(|idx| (idx, translator.uppercase(translator.index_to_char(idx))))
+        .collect();
+
+    let fits_qwerty = letters.iter().all(|(_, letter)| letter.len() == 1 && QWERTY.iter().any(|row| row.contains(letter.as_str())));
+    if fits_qwerty {
+        return QWERTY.iter()
+            .map(|row| row.chars().filter_map(|ch| letters.iter().find(|(_, letter)| letter.starts_with(ch)).map(|&(idx, _)| idx)).collect())
+            .collect();
+    }
+
+    // Without an alphabet, the letters are in the order they were found
+    if game.manifest.alphabet.is_none() {
+        letters.sort_by(|a, b| a.1.cmp(&b.1));
+    }
+    letters.chunks(10).map(|row| row.iter().map(|&(idx, _)| idx).collect()).collect()
+}
+
+// Splits a text into lines of at most `width` characters, between words
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            },
+            _ => lines.push(word.to_owned()),
+        }
+    }
+    lines
+}
+
+// The number of columns that a tile takes, where East Asian letters
+// are twice as wide and combining marks don't take any space
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|ch| match ch as u32 {
+            _ if is_combining_mark(ch) => 0,
+            0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+// The colors of the tiles, the same ones as in plain mode
+fn tile_color(color: WordleColor) -> Color {
+    match color {
+        Colors::GRAY => Color::AnsiValue(242),
+        Colors::YELLOW => Color::AnsiValue(178),
+        Colors::GREEN => Color::AnsiValue(71),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,46 @@
+use crate::benchmark::Benchmark;
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct CheatSheet {
+    opener: String,
+    buckets: Vec<CheatSheetRow>,
+}
+
+// A row of the cheat sheet, with the words already translated
+// so that it can be written as JSON directly
+#[derive(Serialize)]
+struct CheatSheetRow {
+    pattern: String,
+    solutions: usize,
+    guess: String,
+    entropy: f64,
+}
+
+// Prints the best second guess for every pattern that the opening
+// word can produce, either as a text table or as JSON
+pub fn print_cheat_sheet(bench: &Benchmark, json: bool) {
+    let translator = bench.translator();
+    let rows: Vec<CheatSheetRow> = bench.opener_buckets().into_iter()
+        .map(|bucket| CheatSheetRow {
+            pattern: bucket.pattern.as_string(),
+            solutions: bucket.solutions.len(),
+            guess: bucket.guess.as_string(translator),
+            entropy: bucket.entropy,
+        }).collect();
+
+    if json {
+        let sheet = CheatSheet { opener: bench.opener().as_string(translator), buckets: rows };
+        println!("{}", serde_json::to_string_pretty(&sheet).unwrap());
+        return;
+    }
+
+    println!("Opening word: {}", bench.opener().as_string(translator));
+    println!("g: Green, y: Yellow, x: Gray");
+    println!();
+    println!("{:<8} {:>9}  {:<8} {:>7}", "Pattern", "Solutions", "Guess", "Bits");
+    for row in &rows {
+        println!("{:<8} {:>9}  {:<8} {:>7.3}", row.pattern, row.solutions, row.guess, row.entropy);
+    }
+}
@@ -5,4 +5,16 @@ impl Colors {
     pub const GRAY: Color = 0;
     pub const YELLOW: Color = 1;
     pub const GREEN: Color = 2;
-}
\ No newline at end of file
+}
+
+// How letters that appear several times in a guess are colored
+#[derive(Copy, Clone, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateRule {
+    // Like the original Wordle, a letter is only yellow as many times
+    // as it appears in the solution without being green
+    #[default]
+    Standard,
+    // Every instance of a letter that is in the solution is yellow
+    Lenient,
+}
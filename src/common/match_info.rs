@@ -1,4 +1,4 @@
-use crate::common::{Word, Pattern, Colors};
+use crate::common::{Word, Pattern, Colors, DuplicateRule};
 
 type MatrixCell = u8;
 
@@ -15,7 +15,8 @@ impl MatrixData {
 pub struct MatchInfo {
     pub matrix: Matrix<MatrixCell>,
     pub counters: Vec<u8>,
-    pub yellow_chars: Vec<usize>
+    pub yellow_chars: Vec<usize>,
+    pub green_positions: Vec<usize>
 }
 
 // A 2D-like structure backed by a linear vector
@@ -26,14 +27,15 @@ pub struct Matrix<T: Copy> {
 }
 
 impl MatchInfo {
-    pub fn from_word_match(word: &Word, pattern: &Pattern, n_chars: usize) -> Self {
-        let matrix = Matrix::new(n_chars, 5, MatrixData::UNKNOWN);
+    pub fn from_word_match(word: &Word, pattern: &Pattern, n_chars: usize, rule: DuplicateRule) -> Self {
+        let matrix = Matrix::new(n_chars, word.len, MatrixData::UNKNOWN);
         let counters = vec![0; n_chars];
-        let yellow_chars = Vec::with_capacity(5);
+        let yellow_chars = Vec::with_capacity(word.len);
+        let green_positions = Vec::with_capacity(word.len);
 
         // Initialization
-        let mut data = Self { matrix, counters, yellow_chars };
-        for (i, (&ch, &color)) in word.chars.iter().zip(pattern.colors.iter()).enumerate() {
+        let mut data = Self { matrix, counters, yellow_chars, green_positions };
+        for (i, (&ch, &color)) in word.letters().iter().zip(pattern.colors.iter()).enumerate() {
             let idx = ch as usize;
             match color {
                 Colors::GRAY => {
@@ -54,6 +56,18 @@ impl MatchInfo {
             }
         }
 
+        // With the lenient rule, several yellows for the same letter
+        // don't mean that it appears several times in the solution, only
+        // that it appears at least once, or as many times as it was green
+        if rule == DuplicateRule::Lenient {
+            for &idx in &data.yellow_chars {
+                let greens = data.green_positions.iter()
+                    .filter(|&&i| word.chars[i] as usize == idx)
+                    .count() as u8;
+                data.counters[idx] = greens.max(1);
+            }
+        }
+
         data
     }
 
@@ -63,7 +77,7 @@ impl MatchInfo {
 
         // Stop immediately if one of the letters in the proposed
         // word cannot be in its current position
-        for (i, &ch) in word.chars.iter().enumerate() {
+        for (i, &ch) in word.letters().iter().enumerate() {
             let idx = ch as usize;
             if self.matrix.get(idx, i) == MatrixData::MISS {
                 return false;
@@ -81,11 +95,25 @@ impl MatchInfo {
             .all(|idx| counters[idx] >= self.counters[idx])
     }
 
+    // Determines if a word can be played in hard mode after this pattern,
+    // which requires reusing the green letters in the same positions
+    // and including at least as many of the letters that were revealed.
+    // Unlike with `matches`, gray letters can still be used.
+    pub fn allows_in_hard_mode(&self, word: &Word) -> bool {
+        let mut counters = vec![0; self.counters.len()];
+        word.letters().iter().for_each(|&ch| counters[ch as usize] += 1);
+
+        self.green_positions.iter()
+            .all(|&i| self.matrix.get(word.chars[i] as usize, i) == MatrixData::EXACT)
+        && self.counters.iter().zip(counters.iter())
+            .all(|(&required, &count)| count >= required)
+    }
+
     // Auxiliary methods to update the internal data during initialization
     // Sets the entire row for this character to NO, taking care not
     // to overwrite green/EXACT matches
     fn set_gray(&mut self, idx: usize) {
-        (0..5).for_each(|i| {
+        (0..self.matrix.cols).for_each(|i| {
             if self.matrix.get(idx, i) != MatrixData::EXACT {
                 self.matrix.set(idx, i, MatrixData::MISS);
             }
@@ -107,6 +135,7 @@ impl MatchInfo {
     // for the character that was the green match
     fn set_green(&mut self, idx: usize, i: usize) {
         self.counters[idx] += 1;
+        self.green_positions.push(i);
         (0..self.matrix.rows).for_each(|other_idx| self.matrix.set(other_idx, i, MatrixData::MISS));
         self.matrix.set(idx, i, MatrixData::EXACT);
     }
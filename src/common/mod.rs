@@ -4,7 +4,7 @@ mod colors;
 mod match_info;
 
 // Re-export the main structs and functions
-pub use word::Word;
+pub use word::{Word, MAX_WORD_LEN};
 pub use pattern::Pattern;
-pub use colors::{Color, Colors};
-pub use match_info::MatchInfo;
\ No newline at end of file
+pub use colors::{Color, Colors, DuplicateRule};
+pub use match_info::MatchInfo;
@@ -1,25 +1,61 @@
-use crate::common::{Color, Colors};
+use crate::common::{Color, Colors, MAX_WORD_LEN};
 
-pub struct Pattern { 
-    pub colors: [Color; 5] 
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    pub colors: [Color; MAX_WORD_LEN],
+    pub len: usize,
 }
 
 impl Pattern {
-    pub fn to_index(&self) -> usize {
-        self.colors[4] as usize * 81 + // 3^4
-        self.colors[3] as usize * 27 + // 3^3
-        self.colors[2] as usize *  9 + // 3^2
-        self.colors[1] as usize *  3 + // 3^1
-        self.colors[0] as usize        // 3^0
+    // Creates an all-gray pattern for a word of the given length
+    pub fn new(len: usize) -> Self {
+        Self { colors: [Colors::GRAY; MAX_WORD_LEN], len }
+    }
+
+    // The total number of possible color patterns for a word length,
+    // which is 3^len, e.g. 243 for five-letter words
+    pub fn count(len: usize) -> usize {
+        3usize.pow(len as u32)
+    }
+
+    // Interprets the colors as the digits of a base-3 number,
+    // with the first position being the least significant one
+    #[inline(always)]
+    pub fn to_index(self) -> usize {
+        self.colors[..self.len].iter().rev()
+            .fold(0, |idx, &color| idx * 3 + color as usize)
     }
 
     pub fn is_solved(&self) -> bool {
-        self.colors.iter().all(|&x| x == Colors::GREEN)
+        self.colors[..self.len].iter().all(|&x| x == Colors::GREEN)
+    }
+
+    // Reads a pattern written like the ones from `as_string`
+    pub fn parse(string: &str) -> Option<Self> {
+        let mut pattern = Self::new(0);
+        for ch in string.chars() {
+            if pattern.len == MAX_WORD_LEN {
+                return None;
+            }
+            pattern.colors[pattern.len] = match ch {
+                'x' => Colors::GRAY,
+                'y' => Colors::YELLOW,
+                'g' => Colors::GREEN,
+                _ => return None,
+            };
+            pattern.len += 1;
+        }
+        Some(pattern)
     }
-}
 
-impl Default for Pattern {
-    fn default() -> Self {
-        Self { colors: [Colors::GRAY; 5] }
+    // Represents the pattern using the same keys that are used
+    // to input it, i.e., "gyxxg"
+    pub fn as_string(&self) -> String {
+        self.colors[..self.len].iter().map(|&color| match color {
+            Colors::GRAY => 'x',
+            Colors::YELLOW => 'y',
+            Colors::GREEN => 'g',
+            _ => unreachable!()
+        }).collect()
     }
-}
\ No newline at end of file
+}
@@ -1,77 +1,195 @@
-use crate::common::{Pattern, Colors};
+use crate::common::{Pattern, Colors, DuplicateRule};
 use crate::dataloader::CharTranslator;
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+// The longest words that are supported. Words are stored in fixed-size
+// arrays so that they can be copied around cheaply, and only the first
+// `len` characters are used.
+pub const MAX_WORD_LEN: usize = 8;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Word {
-    pub chars: [u16; 5], 
+    pub chars: [u16; MAX_WORD_LEN],
+    pub len: usize,
 }
 
 impl Word {
     pub fn from_str(string: &str, translator: &CharTranslator) -> Self {
-        let vu: Vec<u16> = string.chars()
-            .map(|ch| translator.char_to_index(ch))
-            .collect();
-        let chars = [vu[0], vu[1], vu[2], vu[3], vu[4]];
-        Self { chars }
+        let mut chars = [0; MAX_WORD_LEN];
+        let mut len = 0;
+        for (i, tile) in translator.tiles(string).into_iter().enumerate() {
+            chars[i] = translator.char_to_index(tile);
+            len += 1;
+        }
+        Self { chars, len }
     }
 
-    pub fn as_string(&self, translator: &CharTranslator) -> String {
-        self.chars.iter()
-            .map(|&idx| translator.index_to_char(idx).to_uppercase().to_string())
+    // The indices of the characters that are actually in the word
+    pub fn letters(&self) -> &[u16] {
+        &self.chars[..self.len]
+    }
+
+    // The tiles of the word in uppercase, using its original spelling if
+    // some of its letters were folded into others that are equivalent
+    pub fn tiles(&self, translator: &CharTranslator) -> Vec<String> {
+        if let Some(spelling) = translator.spelling(self) {
+            return translator.tiles(spelling).into_iter().map(|tile| translator.uppercase(tile)).collect();
+        }
+
+        self.letters().iter()
+            .map(|&idx| translator.uppercase(translator.index_to_char(idx)))
             .collect()
     }
 
+    pub fn as_string(&self, translator: &CharTranslator) -> String {
+        translator.compose(&self.tiles(translator))
+    }
+
     // Computes the color pattern that you would get if you used
     // this word against the provided solution
-    // We also have to know the total number of possible characters,
-    // to construct the count list with the appropriate size
-    pub fn compute_pattern(&self, solution: &Word) -> Pattern {
-        // counts used to be a vec defined as vec![0; n_chars]
-        // but it turns out that using an array here greatly improves
-        // efficiency, especially when calculating the opening word,
-        // which in turn gives a much better responsiveness on startup.
-        // I figured that 256 should be a reasonable margin to accomodate
-        // most languages. If you're reading this code because you added
-        // a language with more than 256 characters, please, leave an
-        // issue or a PR and I'll be happy to change it back or do
-        // something else about it :)
-        let mut counts = [0; 256];
-        let mut pattern = Pattern::default();
-
-        // Initialize the letter counter
-        solution.chars.iter().for_each(|&ch| {
-            counts[ch as usize] += 1;
-        });
+    pub fn compute_pattern(&self, solution: &Word, rule: DuplicateRule) -> Pattern {
+        self.compute_pattern_len(solution, rule, self.len)
+    }
+
+    // Same as `compute_pattern`, but always inlined, so that callers in
+    // hot loops can pass a constant length and get the loops unrolled
+    #[inline(always)]
+    pub fn compute_pattern_len(&self, solution: &Word, rule: DuplicateRule, len: usize) -> Pattern {
+        // Letters used to be counted in an array with an entry for every
+        // letter in the alphabet, which limited the alphabets to 256
+        // letters. Instead, we remember which positions of the solution
+        // have already been matched, which only needs one entry per
+        // letter in the word. Looking for an unmatched position takes a
+        // pass over the solution for every yellow, but words are short.
+        let mut matched = [false; MAX_WORD_LEN];
+        let mut pattern = Pattern::new(len);
+        let letters = &solution.chars[..len];
 
         // Look for exact (green) matches first
-        (0..5).for_each(|i| {
-            if self.chars[i] == solution.chars[i] {
-                let idx = self.chars[i] as usize;
-                counts[idx] -= 1;
+        for i in 0..len {
+            if self.chars[i] == letters[i] {
+                matched[i] = true;
                 pattern.colors[i] = Colors::GREEN;
             }
-        });
+        }
 
         // Now look for yellow matches
-        (0..5).for_each(|i| {
+        for i in 0..len {
             // Add a yellow match if the current position isn't green,
-            // and the current letter is in the solution, and we
-            // haven't matched all instances of that letter yet
+            // and the current letter is in a position of the solution
+            // that hasn't been matched yet, which is then matched.
+            // With the lenient rule, every instance of a letter that
+            // is in the solution is yellow, regardless of the count.
             if pattern.colors[i] != Colors::GREEN {
                 let ch = self.chars[i];
-                let idx = ch as usize;
-                if letter_in_word(ch, solution) && counts[idx] > 0 {
-                    pattern.colors[i] = Colors::YELLOW;
-                    counts[idx] -= 1;
+                match rule {
+                    DuplicateRule::Standard => {
+                        if let Some(j) = (0..len).find(|&j| !matched[j] && letters[j] == ch) {
+                            matched[j] = true;
+                            pattern.colors[i] = Colors::YELLOW;
+                        }
+                    },
+                    DuplicateRule::Lenient if letters.contains(&ch) => pattern.colors[i] = Colors::YELLOW,
+                    DuplicateRule::Lenient => {}
                 }
             }
-        });
+        }
 
         pattern
     }
 }
 
-// Aux function to determine if a word contains a letter in any position
-fn letter_in_word(letter: u16, word: &Word) -> bool {
-    word.chars.iter().any(|ch| *ch == letter)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The way patterns were computed before, counting the letters in an
+    // array with an entry for every letter of the alphabet
+    fn counting_pattern(guess: &Word, solution: &Word, rule: DuplicateRule) -> Pattern {
+        let len = guess.len;
+        let mut counts = [0u8; 256];
+        let mut pattern = Pattern::new(len);
+        let letters = &solution.chars[..len];
+        for &ch in letters {
+            counts[ch as usize] += 1;
+        }
+        for i in 0..len {
+            if guess.chars[i] == solution.chars[i] {
+                counts[guess.chars[i] as usize] -= 1;
+                pattern.colors[i] = Colors::GREEN;
+            }
+        }
+        for i in 0..len {
+            if pattern.colors[i] != Colors::GREEN {
+                let ch = guess.chars[i];
+                let idx = ch as usize;
+                if letters.contains(&ch) {
+                    match rule {
+                        DuplicateRule::Standard if counts[idx] > 0 => {
+                            pattern.colors[i] = Colors::YELLOW;
+                            counts[idx] -= 1;
+                        },
+                        DuplicateRule::Lenient => pattern.colors[i] = Colors::YELLOW,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        pattern
+    }
+
+    // Words of every length from a small alphabet, so that most of them
+    // repeat some letter
+    fn random_words(n: usize, n_letters: u64) -> Vec<Word> {
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..n).map(|i| {
+            let len = 1 + i % MAX_WORD_LEN;
+            let mut chars = [0; MAX_WORD_LEN];
+            for ch in &mut chars[..len] {
+                *ch = (next() % n_letters) as u16;
+            }
+            Word { chars, len }
+        }).collect()
+    }
+
+    #[test]
+    fn patterns_match_the_counting_version() {
+        for n_letters in [2, 3, 5, 26] {
+            let words = random_words(800, n_letters);
+            for rule in [DuplicateRule::Standard, DuplicateRule::Lenient] {
+                for guess in &words {
+                    for solution in words.iter().filter(|w| w.len == guess.len) {
+                        let expected = counting_pattern(guess, solution, rule);
+                        let pattern = guess.compute_pattern(solution, rule);
+                        assert!(pattern.colors == expected.colors, "{:?} against {:?}", guess.letters(), solution.letters());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_letters() {
+        let word = |chars: &[u16]| {
+            let mut word = Word { chars: [0; MAX_WORD_LEN], len: chars.len() };
+            word.chars[..chars.len()].copy_from_slice(chars);
+            word
+        };
+        let (x, y, g) = (Colors::GRAY, Colors::YELLOW, Colors::GREEN);
+
+        // A guess with two of a letter that the solution has once, in
+        // another position, only gets one yellow with the standard rule
+        let (guess, solution) = (word(&[1, 1, 2, 3, 4]), word(&[5, 6, 7, 8, 1]));
+        assert_eq!(guess.compute_pattern(&solution, DuplicateRule::Standard).colors[..5], [y, x, x, x, x]);
+        assert_eq!(guess.compute_pattern(&solution, DuplicateRule::Lenient).colors[..5], [y, y, x, x, x]);
+
+        // A green match uses up the letter before the yellows are found
+        let (guess, solution) = (word(&[1, 2, 1, 3, 4]), word(&[5, 6, 1, 7, 8]));
+        assert_eq!(guess.compute_pattern(&solution, DuplicateRule::Standard).colors[..5], [x, x, g, x, x]);
+    }
+}
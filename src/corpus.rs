@@ -0,0 +1,10 @@
+use crate::dataloader::CorpusReport;
+
+// Prints how many words were found in a corpus and how many of them
+// ended up in the language's word lists
+pub fn print_corpus_report(lang: &str, report: &CorpusReport) {
+    println!("Read {} words, {} of them different with the right length and letters", report.n_tokens, report.n_candidates);
+    println!("- valid.txt: {} words", report.n_valid);
+    println!("- solutions.txt: {} words, which appear at least {} times", report.n_solutions, report.answer_cutoff);
+    println!("Built {} into {}", lang, report.folder);
+}
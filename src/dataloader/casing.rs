@@ -0,0 +1,55 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// The rules used to change the case of the words, which depend on the
+// language. Letters are always converted one by one, since the words
+// are split into tiles that must keep their identity regardless of
+// their position in the word.
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
+pub enum Casing {
+    #[default]
+    Default,
+    // Turkish and Azerbaijani, where the dotted and dotless i are
+    // different letters, so i/İ and ı/I are the case pairs
+    Turkic,
+    // Greek, where the final sigma is the same letter as the sigma
+    Greek,
+}
+
+impl Casing {
+    pub fn from_locale(locale: Option<&str>) -> Self {
+        // Only the language part matters, e.g. "tr" in "tr-TR"
+        let lang = locale.map(|locale| locale.split(['-', '_']).next().unwrap().to_lowercase());
+        match lang.as_deref() {
+            Some("tr") | Some("az") => Casing::Turkic,
+            Some("el") => Casing::Greek,
+            _ => Casing::Default,
+        }
+    }
+
+    pub fn lowercase(self, word: &str) -> String {
+        word.chars().map(|ch| match (self, ch) {
+            (Casing::Turkic, 'I') => "ı".to_owned(),
+            (Casing::Turkic, 'İ') => "i".to_owned(),
+            (Casing::Greek, 'ς') => "σ".to_owned(),
+            _ => ch.to_lowercase().to_string(),
+        }).collect()
+    }
+
+    // Turns a tile to uppercase, as long as it's still a single glyph,
+    // so that the words keep their width when they are shown
+    pub fn uppercase(self, tile: &str) -> String {
+        let upper = match (self, tile) {
+            (Casing::Turkic, "i") => "İ".to_owned(),
+            (Casing::Turkic, "ı") => "I".to_owned(),
+            // The capital sharp s is used instead of "SS"
+            (_, "ß") => "ẞ".to_owned(),
+            _ => tile.to_uppercase(),
+        };
+
+        if upper.graphemes(true).count() == tile.graphemes(true).count() {
+            upper
+        } else {
+            tile.to_owned()
+        }
+    }
+}
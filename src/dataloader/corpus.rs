@@ -0,0 +1,144 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use rustc_hash::FxHashMap;
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::import::{check_overwrite, create_folder};
+use super::sources::LanguageSource;
+use super::{allowed_letters, build_translator, check_answers_in_guesses, parse_word_list, DataLoader, ListOverrides, LoadError};
+
+// The files that are generated from a corpus
+const FILES: [&str; 3] = ["valid.txt", "freq.txt", "solutions.txt"];
+
+// How the word lists are built from a corpus
+pub struct CorpusOptions {
+    // Words that appear fewer times than this are left out, since
+    // they are usually typos or foreign words
+    pub min_count: usize,
+    // How many of the most frequent words are used as solutions
+    pub n_answers: usize,
+    // Whether the existing word lists can be replaced
+    pub force: bool,
+}
+
+// The results of building the word lists from a corpus
+pub struct CorpusReport {
+    pub folder: String,
+    pub n_tokens: usize,
+    // How many different words with the right length and letters were found
+    pub n_candidates: usize,
+    pub n_valid: usize,
+    pub n_solutions: usize,
+    // The number of times that the least frequent solution appears
+    pub answer_cutoff: usize,
+}
+
+// How often a word appears in the corpus
+struct WordCount {
+    // The word as it's written in lowercase, before it's decomposed
+    spelling: String,
+    count: usize,
+    // Whether the word ever appears in lowercase, since the ones that
+    // are always capitalized are usually names
+    lowercase: bool,
+}
+
+impl DataLoader {
+    // Builds the word lists of a language from a plain text corpus,
+    // using the length and alphabet of the language's manifest. Every
+    // word that appears often enough is valid, the most frequent ones
+    // are the solutions, and freq.txt lists all of them from the most
+    // to the least frequent, along with the times that they appear.
+    pub fn build_language(&self, corpus: &Path, data_dir: &Path, lang: &str, options: &CorpusOptions) -> Result<CorpusReport, LoadError> {
+        let corpus_path = corpus.display().to_string();
+        let unreadable = |e: std::io::Error| LoadError::UnreadableFile { path: corpus_path.clone(), reason: e.to_string() };
+        let file = File::open(corpus).map_err(unreadable)?;
+
+        let folder = data_dir.join(lang.to_lowercase());
+        let source = LanguageSource::Folder(folder.clone());
+        check_overwrite(&source, &FILES, options.force)?;
+
+        let manifest = self.read_manifest(&source)?;
+        let tokenizer = manifest.tokenizer();
+        let casing = manifest.casing();
+        let alphabet = allowed_letters(&manifest, &tokenizer);
+
+        // Count the words with the right length and letters, using the
+        // form that they have in the game to tell them apart
+        let mut counts: FxHashMap<String, WordCount> = FxHashMap::default();
+        let mut n_tokens = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(unreadable)?;
+            for word in line.unicode_words() {
+                n_tokens += 1;
+                let spelling = casing.lowercase(word);
+                let decomposed = tokenizer.decompose(&spelling);
+                let tiles = tokenizer.split(&decomposed);
+                let valid_letters = match &alphabet {
+                    Some(alphabet) => tiles.iter().all(|tile| alphabet.contains(tile)),
+                    None => decomposed.chars().all(|ch| ch.is_alphabetic() || is_combining_mark(ch)),
+                };
+                if tiles.len() != manifest.word_length || !valid_letters {
+                    continue;
+                }
+
+                let lowercase = spelling == word;
+                let entry = counts.entry(decomposed).or_insert_with(|| WordCount { spelling, count: 0, lowercase });
+                entry.count += 1;
+                entry.lowercase |= lowercase;
+            }
+        }
+
+        let n_candidates = counts.len();
+        let mut words: Vec<WordCount> = counts.into_values()
+            .filter(|word| word.lowercase && word.count >= options.min_count)
+            .collect();
+        words.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.spelling.cmp(&b.spelling)));
+        if words.is_empty() {
+            return Err(LoadError::BadCorpus {
+                path: corpus_path,
+                reason: format!("no words with {} letters appear at least {} times in lowercase", manifest.word_length, options.min_count),
+            });
+        }
+
+        let answers = &words[..options.n_answers.min(words.len())];
+        let mut solutions: Vec<&str> = answers.iter().map(|word| word.spelling.as_str()).collect();
+        let mut valid: Vec<&str> = words.iter().map(|word| word.spelling.as_str()).collect();
+        solutions.sort_unstable();
+        valid.sort_unstable();
+
+        let contents = [
+            valid.iter().map(|word| format!("{}\n", word)).collect::<String>(),
+            words.iter().map(|word| format!("{} {}\n", word.spelling, word.count)).collect(),
+            solutions.iter().map(|word| format!("{}\n", word)).collect(),
+        ];
+
+        // Check the new lists like when loading them, since with --force
+        // they replace the ones of a language that may already work
+        let guesses = parse_word_list(&contents[0], &source.path_of(FILES[0]), &manifest)?;
+        let solution_words = parse_word_list(&contents[2], &source.path_of(FILES[2]), &manifest)?;
+        let translator = build_translator(&manifest, &guesses, &solution_words);
+        check_answers_in_guesses(&guesses, &solution_words, &translator, &source.path_of(FILES[0]))?;
+
+        create_folder(&folder)?;
+        for (file, content) in FILES.iter().zip(&contents) {
+            source.write(file, content).map_err(|e| LoadError::UnwritableFile {
+                path: source.path_of(file),
+                reason: e.to_string(),
+            })?;
+        }
+
+        // Make sure that the language can be loaded with the new lists
+        let language = self.load_source(&source, None, &ListOverrides::default())?;
+        Ok(CorpusReport {
+            folder: folder.display().to_string(),
+            n_tokens,
+            n_candidates,
+            n_valid: language.guesses.len(),
+            n_solutions: language.solutions.len(),
+            answer_cutoff: answers.last().map_or(0, |word| word.count),
+        })
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+// A day in the proleptic Gregorian calendar, which is all that's needed
+// to know which answers were already used. Fields are ordered so that
+// comparing dates compares them chronologically.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    // The current day in UTC, which is close enough to the local day,
+    // and can be overridden when it isn't
+    pub fn today() -> Self {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self::from_days((secs / 86400) as i64)
+    }
+
+    // Converts a number of days since 1970-01-01 to a date, following
+    // Howard Hinnant's algorithm, which works with 400-year eras since
+    // the calendar repeats itself every 400 years
+    fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months are counted from March, so that the leap day is the last one
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Dates are written as YYYY-MM-DD
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("\"{}\" is not a valid date, expected YYYY-MM-DD", s);
+        let mut parts = s.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+        let (year, month, day) = match (parts.next().flatten(), parts.next().flatten(), parts.next().flatten()) {
+            (Some(year), Some(month), Some(day)) => (i64::from(year), month, day),
+            _ => return Err(invalid()),
+        };
+
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+
+        Ok(Self { year, month, day })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Dates are saved the same way that they are written
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn days_since_the_epoch() {
+        assert!(Date::from_days(0) == date("1970-01-01"));
+        assert!(Date::from_days(-1) == date("1969-12-31"));
+        assert!(Date::from_days(11016) == date("2000-02-29"));
+        assert!(Date::from_days(11017) == date("2000-03-01"));
+        assert!(Date::from_days(19782) == date("2024-02-29"));
+        assert!(Date::from_days(20089) == date("2025-01-01"));
+    }
+
+    // Every day is followed by the next one in the calendar, across the
+    // ends of the months and the leap years, including 1900 and 2000
+    #[test]
+    fn consecutive_days() {
+        let mut prev = Date::from_days(-30000);
+        for days in -29999..30000 {
+            let next = Date::from_days(days);
+            let expected = if prev.day < days_in_month(prev.year, prev.month) {
+                Date { day: prev.day + 1, ..prev }
+            } else if prev.month < 12 {
+                Date { year: prev.year, month: prev.month + 1, day: 1 }
+            } else {
+                Date { year: prev.year + 1, month: 1, day: 1 }
+            };
+            assert!(next == expected, "{} follows {}", next, prev);
+            assert!(next > prev);
+            prev = next;
+        }
+    }
+
+    #[test]
+    fn leap_days() {
+        assert!("2024-02-29".parse::<Date>().is_ok());
+        assert!("2000-02-29".parse::<Date>().is_ok());
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("1900-02-29".parse::<Date>().is_err());
+    }
+
+    #[test]
+    fn month_ends() {
+        for month in ["01-31", "03-31", "04-30", "06-30", "09-30", "11-30", "12-31"] {
+            assert!(format!("2026-{}", month).parse::<Date>().is_ok(), "{}", month);
+        }
+        for month in ["04-31", "06-31", "09-31", "11-31", "01-32", "02-30"] {
+            assert!(format!("2026-{}", month).parse::<Date>().is_err(), "{}", month);
+        }
+    }
+
+    #[test]
+    fn invalid_dates() {
+        for s in ["", "2026", "2026-10", "2026-00-10", "2026-13-01", "2026-10-00", "2026-1o-01", "-2026-10-01", "2026-10-19-01"] {
+            assert!(s.parse::<Date>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn written_as_parsed() {
+        assert_eq!(date("2026-01-05").to_string(), "2026-01-05");
+        assert_eq!(date("0999-12-31").to_string(), "0999-12-31");
+        assert_eq!(serde_json::to_string(&date("2026-10-19")).unwrap(), "\"2026-10-19\"");
+        assert!(serde_json::from_str::<Date>("\"2026-10-19\"").unwrap() == date("2026-10-19"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::fmt;
+
+// The reasons why a language can fail to load
+#[derive(Debug)]
+pub enum LoadError {
+    // None of the data folders contains the language
+    MissingLanguage { lang: String, available: Vec<String> },
+    // One of the language's files doesn't exist or can't be read
+    UnreadableFile { path: String, reason: String },
+    // One of the language's files can't be written when fixing it
+    UnwritableFile { path: String, reason: String },
+    // The language's manifest can't be parsed or has invalid values
+    BadManifest { path: String, reason: String },
+    // A line in one of the files is not a valid word
+    BadWord { path: String, line: usize, word: String, reason: String },
+    // One of the files doesn't contain any word
+    EmptyList { path: String },
+    // The list of valid guesses contains some of the solutions, but not all
+    MissingAnswers { path: String, words: Vec<String> },
+    // The word lists can't be found in the code of a Wordle
+    BadBundle { path: String, reason: String },
+    // The word lists can't be built from a text
+    BadCorpus { path: String, reason: String },
+    // A word that was going to be recorded as used isn't an answer
+    NotAnAnswer { word: String },
+    // A word provided by the user can't be in the language's lists
+    InvalidWord { word: String, reason: String },
+    // The schedule doesn't say what the answer is on some day
+    NoScheduledAnswer { path: String, day: String },
+    // The language doesn't have the requested version of the answers
+    MissingVariant { path: String, available: Vec<String> },
+    // A file of benchmark results can't be parsed
+    BadReport { path: String, reason: String },
+    // The benchmark results were saved without a guess model
+    NoGuessModel { path: String },
+    // There is no game to resume
+    NoSession { path: String },
+    // The saved game can't be parsed or has an invalid turn
+    BadSession { path: String, reason: String },
+    // The key bindings file can't be parsed or binds a key twice
+    BadKeyBindings { path: String, reason: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::MissingLanguage { lang, available } => {
+                write!(f, "Could not find the language \"{}\" in any of the data folders.", lang)?;
+                if available.is_empty() {
+                    write!(f, " No languages are available, use --data-dir or ELDROW_DATA to point to a data folder.")
+                } else {
+                    write!(f, " Available languages: {}", available.join(", "))
+                }
+            },
+            LoadError::UnreadableFile { path, reason } =>
+                write!(f, "Could not read {}: {}", path, reason),
+            LoadError::UnwritableFile { path, reason } =>
+                write!(f, "Could not write {}: {}", path, reason),
+            LoadError::BadManifest { path, reason } =>
+                write!(f, "{} is not a valid manifest: {}", path, reason),
+            LoadError::BadWord { path, line, word, reason } =>
+                write!(f, "{}, line {}: \"{}\" {}", path, line, word, reason),
+            LoadError::EmptyList { path } =>
+                write!(f, "{} does not contain any word", path),
+            LoadError::MissingAnswers { path, words } => {
+                let shown = words.iter().take(10).cloned().collect::<Vec<_>>().join(", ");
+                let more = if words.len() > 10 { format!(" and {} more", words.len() - 10) } else { String::new() };
+                write!(f, "{} contains some of the solutions, but is missing {}{}", path, shown, more)
+            },
+            LoadError::BadBundle { path, reason } =>
+                write!(f, "Could not import the word lists from {}: {}", path, reason),
+            LoadError::BadCorpus { path, reason } =>
+                write!(f, "Could not build the word lists from {}: {}", path, reason),
+            LoadError::NotAnAnswer { word } =>
+                write!(f, "\"{}\" is not one of the answers", word),
+            LoadError::InvalidWord { word, reason } =>
+                write!(f, "\"{}\" {}", word, reason),
+            LoadError::NoScheduledAnswer { path, day } =>
+                write!(f, "{} does not have an answer for {}", path, day),
+            LoadError::MissingVariant { path, available } => {
+                write!(f, "Could not find {}.", path)?;
+                if available.is_empty() {
+                    write!(f, " The language only has its default answers")
+                } else {
+                    write!(f, " Available variants: {}", available.join(", "))
+                }
+            },
+            LoadError::BadReport { path, reason } =>
+                write!(f, "{} is not a valid benchmark result: {}", path, reason),
+            LoadError::NoGuessModel { path } =>
+                write!(f, "{} does not contain a guess model, run the benchmark again to save one", path),
+            LoadError::NoSession { path } =>
+                write!(f, "There is no saved game to resume in {}", path),
+            LoadError::BadSession { path, reason } =>
+                write!(f, "{} is not a valid session file: {}", path, reason),
+            LoadError::BadKeyBindings { path, reason } =>
+                write!(f, "{} is not a valid key bindings file: {}", path, reason),
+        }
+    }
+}
+
+impl Error for LoadError {}
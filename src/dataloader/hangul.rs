@@ -0,0 +1,191 @@
+// Hangul syllables are blocks made of an initial consonant, a vowel and
+// an optional final consonant, which Korean Wordles color separately.
+// Words are split into jamo the same way that they are typed in the
+// standard 2-set keyboard, so the compound vowels and final consonants,
+// which take several keystrokes, are also split, e.g. 닭 -> ㄷㅏㄹㄱ.
+
+const FIRST_SYLLABLE: u32 = 0xAC00;
+const LAST_SYLLABLE: u32 = 0xD7A3;
+
+const INITIALS: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ',
+    'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ', 'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+const VOWELS: [char; 21] = [
+    'ㅏ', 'ㅐ', 'ㅑ', 'ㅒ', 'ㅓ', 'ㅔ', 'ㅕ', 'ㅖ', 'ㅗ', 'ㅘ', 'ㅙ',
+    'ㅚ', 'ㅛ', 'ㅜ', 'ㅝ', 'ㅞ', 'ㅟ', 'ㅠ', 'ㅡ', 'ㅢ', 'ㅣ',
+];
+
+// The first one stands for syllables without a final consonant
+const FINALS: [Option<char>; 28] = [
+    None, Some('ㄱ'), Some('ㄲ'), Some('ㄳ'), Some('ㄴ'), Some('ㄵ'), Some('ㄶ'),
+    Some('ㄷ'), Some('ㄹ'), Some('ㄺ'), Some('ㄻ'), Some('ㄼ'), Some('ㄽ'), Some('ㄾ'),
+    Some('ㄿ'), Some('ㅀ'), Some('ㅁ'), Some('ㅂ'), Some('ㅄ'), Some('ㅅ'), Some('ㅆ'),
+    Some('ㅇ'), Some('ㅈ'), Some('ㅊ'), Some('ㅋ'), Some('ㅌ'), Some('ㅍ'), Some('ㅎ'),
+];
+
+// The jamo that are typed with two keys, and the keys that make them up
+const COMPOUNDS: [(char, char, char); 18] = [
+    ('ㅘ', 'ㅗ', 'ㅏ'), ('ㅙ', 'ㅗ', 'ㅐ'), ('ㅚ', 'ㅗ', 'ㅣ'), ('ㅝ', 'ㅜ', 'ㅓ'),
+    ('ㅞ', 'ㅜ', 'ㅔ'), ('ㅟ', 'ㅜ', 'ㅣ'), ('ㅢ', 'ㅡ', 'ㅣ'), ('ㄳ', 'ㄱ', 'ㅅ'),
+    ('ㄵ', 'ㄴ', 'ㅈ'), ('ㄶ', 'ㄴ', 'ㅎ'), ('ㄺ', 'ㄹ', 'ㄱ'), ('ㄻ', 'ㄹ', 'ㅁ'),
+    ('ㄼ', 'ㄹ', 'ㅂ'), ('ㄽ', 'ㄹ', 'ㅅ'), ('ㄾ', 'ㄹ', 'ㅌ'), ('ㄿ', 'ㄹ', 'ㅍ'),
+    ('ㅀ', 'ㄹ', 'ㅎ'), ('ㅄ', 'ㅂ', 'ㅅ'),
+];
+
+// Splits every syllable in the word into its jamo, leaving anything
+// else untouched
+pub fn decompose(word: &str) -> String {
+    let mut jamo = String::new();
+    for ch in word.chars() {
+        let code = ch as u32;
+        if !(FIRST_SYLLABLE..=LAST_SYLLABLE).contains(&code) {
+            jamo.push(ch);
+            continue;
+        }
+
+        let idx = (code - FIRST_SYLLABLE) as usize;
+        let (initial, vowel, last) = (idx / (21 * 28), idx / 28 % 21, idx % 28);
+        push_keys(&mut jamo, INITIALS[initial]);
+        push_keys(&mut jamo, VOWELS[vowel]);
+        if let Some(last) = FINALS[last] {
+            push_keys(&mut jamo, last);
+        }
+    }
+
+    jamo
+}
+
+// Joins a sequence of jamo back into syllables, the same way that a
+// Korean keyboard does while typing. Jamo that can't be part of a
+// syllable are left as they are.
+pub fn compose(word: &str) -> String {
+    let jamo: Vec<char> = word.chars().collect();
+    let mut syllables = String::new();
+    let mut i = 0;
+
+    while i < jamo.len() {
+        // A syllable needs at least an initial consonant and a vowel
+        let (initial, mut vowel) = match (index_of(&INITIALS, jamo[i]), jamo.get(i + 1).and_then(|&v| index_of(&VOWELS, v))) {
+            (Some(initial), Some(vowel)) => (initial, vowel),
+            _ => {
+                syllables.push(jamo[i]);
+                i += 1;
+                continue;
+            }
+        };
+        i += 2;
+
+        if let Some(compound) = jamo.get(i).and_then(|&next| combine(VOWELS[vowel], next)) {
+            vowel = index_of(&VOWELS, compound).unwrap();
+            i += 1;
+        }
+
+        // A consonant is only the final one if it doesn't start the
+        // next syllable, i.e., if it isn't followed by a vowel
+        let starts_syllable = |pos: usize| jamo.get(pos + 1).is_some_and(|&v| index_of(&VOWELS, v).is_some());
+        let mut last = 0;
+        if let Some(final_idx) = jamo.get(i).and_then(|&c| index_of(&FINALS, Some(c))) {
+            if !starts_syllable(i) {
+                last = final_idx;
+                i += 1;
+
+                let compound = jamo.get(i).and_then(|&next| combine(FINALS[last].unwrap(), next));
+                if let Some(compound) = compound.filter(|_| !starts_syllable(i)) {
+                    last = index_of(&FINALS, Some(compound)).unwrap();
+                    i += 1;
+                }
+            }
+        }
+
+        let code = FIRST_SYLLABLE + ((initial * 21 + vowel) * 28 + last) as u32;
+        syllables.push(char::from_u32(code).unwrap());
+    }
+
+    syllables
+}
+
+// Pushes the keys that are needed to type a jamo
+fn push_keys(jamo: &mut String, ch: char) {
+    match COMPOUNDS.iter().find(|&&(compound, _, _)| compound == ch) {
+        Some(&(_, first, second)) => {
+            jamo.push(first);
+            jamo.push(second);
+        },
+        None => jamo.push(ch),
+    }
+}
+
+// Finds the compound jamo that is typed with the two given keys
+fn combine(first: char, second: char) -> Option<char> {
+    COMPOUNDS.iter()
+        .find(|&&(_, a, b)| a == first && b == second)
+        .map(|&(compound, _, _)| compound)
+}
+
+fn index_of<T: PartialEq>(list: &[T], item: T) -> Option<usize> {
+    list.iter().position(|x| *x == item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syllable(initial: usize, vowel: usize, last: usize) -> char {
+        char::from_u32(FIRST_SYLLABLE + ((initial * 21 + vowel) * 28 + last) as u32).unwrap()
+    }
+
+    #[test]
+    fn split_into_keys() {
+        // With and without a final consonant
+        assert_eq!(decompose("가"), "ㄱㅏ");
+        assert_eq!(decompose("한"), "ㅎㅏㄴ");
+        assert_eq!(decompose("음식"), "ㅇㅡㅁㅅㅣㄱ");
+        // Compound vowels and finals take two keys, but double consonants
+        // have keys of their own
+        assert_eq!(decompose("과"), "ㄱㅗㅏ");
+        assert_eq!(decompose("의"), "ㅇㅡㅣ");
+        assert_eq!(decompose("닭"), "ㄷㅏㄹㄱ");
+        assert_eq!(decompose("값"), "ㄱㅏㅂㅅ");
+        assert_eq!(decompose("뷁"), "ㅂㅜㅔㄹㄱ");
+        assert_eq!(decompose("있"), "ㅇㅣㅆ");
+        assert_eq!(decompose("abc ㄱ"), "abc ㄱ");
+    }
+
+    #[test]
+    fn joined_like_typing() {
+        assert_eq!(compose("ㄷㅏㄹㄱ"), "닭");
+        assert_eq!(compose("ㄱㅗㅏㅇ"), "광");
+        // A consonant followed by a vowel starts the next syllable, even
+        // if it could be part of a compound final
+        assert_eq!(compose("ㄷㅏㄹㄱㅏ"), "달가");
+        assert_eq!(compose("ㅇㅡㅁㅅㅣㄱ"), "음식");
+        // Jamo that can't be part of a syllable are left alone
+        assert_eq!(compose("ㅏㄱ"), "ㅏㄱ");
+        assert_eq!(compose("ㄱㄴㅏ"), "ㄱ나");
+        assert_eq!(compose("abc"), "abc");
+    }
+
+    #[test]
+    fn every_syllable_round_trips() {
+        for code in FIRST_SYLLABLE..=LAST_SYLLABLE {
+            let word = char::from_u32(code).unwrap().to_string();
+            assert_eq!(compose(&decompose(&word)), word);
+        }
+    }
+
+    // The final consonant of a syllable can't be confused with the
+    // initial one of the next, whatever they are
+    #[test]
+    fn syllables_round_trip_together() {
+        for last in 0..FINALS.len() {
+            for initial in 0..INITIALS.len() {
+                for (vowel, next_last) in [(0, 0), (9, 0), (14, 3), (19, 9)] {
+                    let word: String = [syllable(3, vowel, last), syllable(initial, vowel, next_last)].iter().collect();
+                    assert_eq!(compose(&decompose(&word)), word);
+                }
+            }
+        }
+    }
+}
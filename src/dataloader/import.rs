@@ -0,0 +1,368 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use rustc_hash::{FxHashMap, FxHashSet};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::common::MAX_WORD_LEN;
+use super::sources::LanguageSource;
+use super::{build_translator, check_answers_in_guesses, parse_word_list, DataLoader, ListOverrides, LoadError, Manifest};
+
+// Arrays with fewer words than this are not considered word lists,
+// which leaves out things like the rows of the keyboard
+const MIN_LIST_WORDS: usize = 50;
+
+// How many of the answers must be in the list of valid words, or out
+// of it, for the lists to look like the answers and the guesses
+const OVERLAP_RATIO: f64 = 0.9;
+
+// A list of words found in the code of a Wordle
+pub struct FoundList {
+    pub word_length: usize,
+    pub n_words: usize,
+    // The file that the list was written to, if it was picked
+    pub file: Option<&'static str>,
+}
+
+// The results of importing the word lists of a Wordle
+pub struct ImportReport {
+    pub folder: String,
+    pub lists: Vec<FoundList>,
+    // How many of the answers are also in the list of valid words
+    pub n_shared: usize,
+    // Whether a manifest was written to set the length of the words
+    pub wrote_manifest: bool,
+    pub n_guesses: usize,
+    pub n_solutions: usize,
+}
+
+impl DataLoader {
+    // Extracts the word lists from the JS code of a Wordle, writes them
+    // to the language's folder inside the given data folder, and loads
+    // the language to make sure that the lists are valid
+    pub fn import_language(&self, bundle: &Path, data_dir: &Path, lang: &str, force: bool) -> Result<ImportReport, LoadError> {
+        let bundle_path = bundle.display().to_string();
+        let code = read_to_string(bundle).map_err(|e| LoadError::UnreadableFile {
+            path: bundle_path.clone(),
+            reason: e.to_string(),
+        })?;
+
+        let folder = data_dir.join(lang.to_lowercase());
+        let source = LanguageSource::Folder(folder.clone());
+        let unwritable = |file: &str, reason: String| LoadError::UnwritableFile { path: source.path_of(file), reason };
+        check_overwrite(&source, &["valid.txt", "solutions.txt"], force)?;
+
+        // The words are measured the same way as when they are loaded,
+        // using the language's manifest if there is already one
+        let mut manifest = self.read_manifest(&source)?;
+        let lists: Vec<(usize, Vec<String>)> = string_arrays(&code).into_iter()
+            .filter_map(|words| Some((word_length(&words, &manifest)?, words)))
+            .collect();
+
+        let (valid, solutions) = pick_lists(&lists).map_err(|reason| LoadError::BadBundle {
+            path: bundle_path,
+            reason: reason.to_owned(),
+        })?;
+        let (length, valid_words) = &lists[valid];
+        let (_, solution_words) = &lists[solutions];
+
+        // A manifest is written if the words don't have the default
+        // length and there isn't a manifest to set it
+        let wrote_manifest = *length != manifest.word_length && !source.exists("manifest.toml");
+        if wrote_manifest {
+            manifest.word_length = *length;
+        }
+
+        // The lists are checked the same way as when they are loaded
+        // before writing anything, so that a language that already works
+        // isn't replaced with lists that don't
+        let casing = manifest.casing();
+        let contents: Vec<(&str, String)> = [("valid.txt", valid_words), ("solutions.txt", solution_words)].into_iter()
+            .map(|(file, words)| (file, words.iter().map(|word| format!("{}\n", casing.lowercase(word))).collect()))
+            .collect();
+        let guesses = parse_word_list(&contents[0].1, &source.path_of("valid.txt"), &manifest)?;
+        let answers = parse_word_list(&contents[1].1, &source.path_of("solutions.txt"), &manifest)?;
+        let translator = build_translator(&manifest, &guesses, &answers);
+        check_answers_in_guesses(&guesses, &answers, &translator, &source.path_of("valid.txt"))?;
+
+        create_folder(&folder)?;
+        if wrote_manifest {
+            write(folder.join("manifest.toml"), format!("word_length = {}\n", length))
+                .map_err(|e| unwritable("manifest.toml", e.to_string()))?;
+        }
+        for (file, content) in &contents {
+            source.write(file, content).map_err(|e| unwritable(file, e.to_string()))?;
+        }
+
+        let language = self.load_source(&source, None, &ListOverrides::default())?;
+        let valid_set: FxHashSet<&String> = valid_words.iter().collect();
+        Ok(ImportReport {
+            folder: folder.display().to_string(),
+            lists: lists.iter().enumerate()
+                .map(|(i, (word_length, words))| FoundList {
+                    word_length: *word_length,
+                    n_words: words.len(),
+                    file: if i == valid { Some("valid.txt") } else if i == solutions { Some("solutions.txt") } else { None },
+                })
+                .collect(),
+            n_shared: solution_words.iter().filter(|word| valid_set.contains(word)).count(),
+            wrote_manifest,
+            n_guesses: language.guesses.len(),
+            n_solutions: language.solutions.len(),
+        })
+    }
+}
+
+// Makes sure that none of the files exist, unless they can be replaced
+pub(super) fn check_overwrite(source: &LanguageSource, files: &[&str], force: bool) -> Result<(), LoadError> {
+    match files.iter().find(|file| source.exists(file)) {
+        Some(file) if !force => Err(LoadError::UnwritableFile {
+            path: source.path_of(file),
+            reason: "it already exists, use --force to replace it".to_owned(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub(super) fn create_folder(folder: &Path) -> Result<(), LoadError> {
+    create_dir_all(folder).map_err(|e| LoadError::UnwritableFile {
+        path: folder.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+// The number of tiles that all the words in a list have, if it looks
+// like a list of words, i.e., if they all have the same length and are
+// only made of letters
+fn word_length(words: &[String], manifest: &Manifest) -> Option<usize> {
+    let tokenizer = manifest.tokenizer();
+    let casing = manifest.casing();
+    let is_letter = |ch: char| ch.is_alphabetic() || is_combining_mark(ch) || ch == '\'';
+    if words.len() < MIN_LIST_WORDS || words.iter().any(|word| word.is_empty() || !word.chars().all(is_letter)) {
+        return None;
+    }
+
+    let lengths: FxHashSet<usize> = words.iter()
+        .map(|word| tokenizer.split(&tokenizer.decompose(&casing.lowercase(word))).len())
+        .collect();
+    match lengths.into_iter().collect::<Vec<_>>()[..] {
+        [length] if (2..=MAX_WORD_LEN).contains(&length) => Some(length),
+        _ => None,
+    }
+}
+
+// Picks the lists of valid words and answers among the ones found, as
+// the indices of the lists. Only the lists with the most common length
+// are considered. The largest one is the list of valid words, and the
+// answers are the largest of the rest that is either almost contained
+// in it or almost separate from it, falling back to the largest one.
+fn pick_lists(lists: &[(usize, Vec<String>)]) -> Result<(usize, usize), &'static str> {
+    let mut words_by_length: FxHashMap<usize, usize> = FxHashMap::default();
+    for (length, words) in lists {
+        *words_by_length.entry(*length).or_default() += words.len();
+    }
+
+    let length = match words_by_length.into_iter().max_by_key(|&(length, n_words)| (n_words, length)) {
+        Some((length, _)) => length,
+        None => return Err("no lists of words were found"),
+    };
+
+    let mut candidates: Vec<usize> = (0..lists.len()).filter(|&i| lists[i].0 == length).collect();
+    candidates.sort_by_key(|&i| std::cmp::Reverse(lists[i].1.len()));
+    let (valid, rest) = match candidates.split_first() {
+        Some((&valid, rest)) if !rest.is_empty() => (valid, rest),
+        _ => return Err("only one list of words was found, so the answers can't be told apart from the guesses"),
+    };
+
+    let valid_set: FxHashSet<&String> = lists[valid].1.iter().collect();
+    let solutions = rest.iter().copied()
+        .find(|&i| {
+            let words = &lists[i].1;
+            let shared = words.iter().filter(|word| valid_set.contains(word)).count() as f64 / words.len() as f64;
+            shared >= OVERLAP_RATIO || shared <= 1.0 - OVERLAP_RATIO
+        })
+        .unwrap_or(rest[0]);
+
+    Ok((valid, solutions))
+}
+
+// Finds every array literal in the code whose elements are all strings
+fn string_arrays(code: &str) -> Vec<Vec<String>> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut arrays = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '[' {
+            if let Some((strings, end)) = parse_array(&chars, i + 1) {
+                arrays.push(strings);
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    arrays
+}
+
+// Parses the strings in an array literal, starting after its opening
+// bracket, and returns them along with the position after the array
+fn parse_array(chars: &[char], mut i: usize) -> Option<(Vec<String>, usize)> {
+    let skip_spaces = |mut i: usize| {
+        while chars.get(i).is_some_and(|ch| ch.is_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut strings = vec![];
+    loop {
+        i = skip_spaces(i);
+        if chars.get(i) == Some(&']') {
+            return Some((strings, i + 1));
+        }
+
+        let (string, end) = parse_string(chars, i)?;
+        strings.push(string);
+        i = skip_spaces(end);
+        match chars.get(i) {
+            Some(',') => i += 1,
+            Some(']') => return Some((strings, i + 1)),
+            _ => return None,
+        }
+    }
+}
+
+// Parses a string literal that starts at the given position, and returns
+// it along with the position after it. Template literals with
+// placeholders aren't plain strings, so they are not accepted.
+fn parse_string(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let quote = *chars.get(start).filter(|&&ch| matches!(ch, '"' | '\'' | '`'))?;
+    let mut string = String::new();
+    let mut i = start + 1;
+    loop {
+        match *chars.get(i)? {
+            ch if ch == quote => return Some((string, i + 1)),
+            '\n' if quote != '`' => return None,
+            '$' if quote == '`' && chars.get(i + 1) == Some(&'{') => return None,
+            '\\' => {
+                let (ch, len) = parse_escape(chars, i + 1)?;
+                string.extend(ch);
+                i += len;
+            },
+            ch => string.push(ch),
+        }
+        i += 1;
+    }
+}
+
+// Parses the escape sequence after a backslash, returning the character
+// it stands for, if any, and how many characters it takes
+fn parse_escape(chars: &[char], i: usize) -> Option<(Option<char>, usize)> {
+    let hex = |from: usize, len: usize| {
+        let digits: String = chars.get(from..from + len)?.iter().collect();
+        char::from_u32(u32::from_str_radix(&digits, 16).ok()?)
+    };
+
+    Some(match *chars.get(i)? {
+        'n' => (Some('\n'), 1),
+        't' => (Some('\t'), 1),
+        'r' => (Some('\r'), 1),
+        '0' => (Some('\0'), 1),
+        // Escaped line breaks continue the string in the next line
+        '\n' => (None, 1),
+        'x' => (Some(hex(i + 1, 2)?), 3),
+        'u' if chars.get(i + 1) == Some(&'{') => {
+            let len = chars[i + 2..].iter().position(|&ch| ch == '}')?;
+            (Some(hex(i + 2, len)?), len + 3)
+        },
+        'u' => (Some(hex(i + 1, 4)?), 5),
+        ch => (Some(ch), 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(prefix: &str, n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{}{}{}", prefix, (b'a' + (i / 26) as u8) as char, (b'a' + (i % 26) as u8) as char)).collect()
+    }
+
+    #[test]
+    fn arrays_of_string_arrays() {
+        assert_eq!(string_arrays(r#"var a=["one", 'two' ,`three`],b=[];"#), [vec!["one", "two", "three"], vec![]]);
+        assert_eq!(string_arrays("f([\n  'a',\n  'b',\n])"), [vec!["a", "b"]]);
+
+        // Arrays with anything other than strings are left out, but the
+        // arrays of strings inside them are still found
+        assert_eq!(string_arrays(r#"x=[1, "a"]; y=["b", c]; z=[["d"], ["e"]]"#), [vec!["d"], vec!["e"]]);
+        assert!(string_arrays(r#"y = "[not an array"; z = ["unclosed""#).is_empty());
+
+        // Indexing with a string looks the same, which is fine since the
+        // word lists are much longer
+        assert_eq!(string_arrays(r#"x["key"]"#), [vec!["key"]]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(string_arrays(r#"["it\'s", "say \"hi\"", 'a\\b']"#), [vec!["it's", "say \"hi\"", "a\\b"]]);
+        assert_eq!(string_arrays(r#"["été", "\u{1F600}", "\x41", "tab\tnew\nline"]"#), [vec!["été", "😀", "A", "tab\tnew\nline"]]);
+        assert_eq!(string_arrays("[\"con\\\ntinued\"]"), [vec!["continued"]]);
+
+        // Invalid escapes make the string, and the array, unreadable
+        assert!(string_arrays(r#"["\u00zz"]"#).is_empty());
+        assert!(string_arrays(r#"["\u{110000}"]"#).is_empty());
+    }
+
+    // Template literals can span lines, but the ones with placeholders
+    // aren't known until the code runs
+    #[test]
+    fn templates() {
+        assert_eq!(string_arrays("[`multi\nline`, `cost $5`]"), [vec!["multi\nline", "cost $5"]]);
+        assert!(string_arrays("[`hello ${name}`, 'b']").is_empty());
+        assert!(string_arrays("['line\nbreak']").is_empty());
+    }
+
+    #[test]
+    fn lists_of_words() {
+        let manifest = Manifest::default();
+        assert_eq!(word_length(&words("abc", 100), &manifest), Some(5));
+        assert_eq!(word_length(&words("ABC", 100), &manifest), Some(5));
+
+        // The rows of a keyboard are too short, even with words of the
+        // same length, and so are the lists with anything but letters
+        let row: Vec<String> = ["qwertyuiop", "asdfghjklñ"].iter().map(|row| row.to_string()).collect();
+        assert_eq!(word_length(&row, &manifest), None);
+        assert_eq!(word_length(&words("ab", MIN_LIST_WORDS - 1), &manifest), None);
+        let mut mixed = words("abc", 100);
+        mixed.push("ab1de".to_owned());
+        assert_eq!(word_length(&mixed, &manifest), None);
+        mixed.pop();
+        mixed.push("abcdef".to_owned());
+        assert_eq!(word_length(&mixed, &manifest), None);
+    }
+
+    #[test]
+    fn picking_the_lists() {
+        let valid = words("abc", 600);
+        let contained = valid[..100].to_vec();
+        let disjoint = words("xyz", 200);
+        let mixed: Vec<String> = valid[..60].iter().cloned().chain(words("qrs", 60)).collect();
+
+        // The answers can be part of the valid words or a list of their
+        // own, while a list that is half of each is something else
+        let lists = vec![(5, mixed.clone()), (5, contained), (5, valid.clone())];
+        assert_eq!(pick_lists(&lists), Ok((2, 1)));
+        let lists = vec![(5, valid.clone()), (5, mixed.clone()), (5, disjoint)];
+        assert_eq!(pick_lists(&lists), Ok((0, 2)));
+        let lists = vec![(5, valid.clone()), (5, mixed)];
+        assert_eq!(pick_lists(&lists), Ok((0, 1)));
+
+        // Only the lists with the length of most of the words count
+        let lists = vec![(6, words("abcd", 100)), (5, valid.clone()), (6, words("wxyz", 200))];
+        assert!(pick_lists(&lists).is_err());
+        let lists = vec![(5, valid), (6, words("abcd", 1000))];
+        assert!(pick_lists(&lists).is_err());
+        assert!(pick_lists(&[]).is_err());
+    }
+}
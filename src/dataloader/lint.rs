@@ -0,0 +1,320 @@
+use std::collections::hash_map::Entry;
+use std::io::ErrorKind;
+use rustc_hash::{FxHashMap, FxHashSet};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{is_nfc, is_nfd, UnicodeNormalization};
+
+use crate::common::Word;
+use super::sources::LanguageSource;
+use super::{allowed_letters, lacks_answers, CharTranslator, DataLoader, LoadError, Manifest};
+
+// The files that contain the word lists, in the order they are checked
+const FILES: [&str; 2] = ["valid.txt", "solutions.txt"];
+
+// Letters that appear in fewer words than this are reported as unusual
+const RARE_LETTER_WORDS: usize = 3;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    WrongLength,
+    OutsideAlphabet,
+    Duplicate,
+    MissingAnswer,
+    MixedNormalization,
+    UnusualLetter,
+}
+
+// A problem found in one of the word lists
+pub struct LintIssue {
+    pub kind: IssueKind,
+    pub path: String,
+    // The line of the file, which missing words don't have
+    pub line: Option<usize>,
+    pub message: String,
+    file: usize,
+    word: String,
+}
+
+// The results of checking a language's word lists
+pub struct LintReport {
+    pub issues: Vec<LintIssue>,
+    // How many issues were fixed, if fixing them was requested
+    pub fixed: Option<usize>,
+    pub alphabet_size: usize,
+    pub n_valid: usize,
+    pub n_solutions: usize,
+    pub n_guesses: usize,
+}
+
+// The Unicode normalization form that the words are written in
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Form {
+    Nfc,
+    Nfd,
+}
+
+impl DataLoader {
+    // Checks the word lists of a language, and optionally fixes them by
+    // normalizing and lowercasing the words, removing the ones that are
+    // invalid or duplicated, and adding the missing answers to valid.txt
+    pub fn lint_language(&self, lang: &str, fix: bool) -> Result<LintReport, LoadError> {
+        let source = self.find_language(lang)?;
+        let manifest = self.read_manifest(&source)?;
+        // The answers may be missing if there are word frequencies, in
+        // which case the list is empty and is never written
+        let mut lists = vec![];
+        let mut missing = vec![];
+        for file in FILES {
+            let optional = file == "solutions.txt" && source.exists("freq.txt");
+            let content = match source.read(file) {
+                Err(e) if optional && e.kind() == ErrorKind::NotFound => String::new(),
+                content => content.map_err(|e| LoadError::UnreadableFile {
+                    path: source.path_of(file),
+                    reason: e.to_string(),
+                })?,
+            };
+            missing.push(optional && !source.exists(file));
+            lists.push(content.lines().map(str::to_owned).collect::<Vec<_>>());
+        }
+
+        let report = check(&manifest, &source, &lists);
+        if !fix {
+            return Ok(report);
+        }
+
+        let fixed = fix_lists(&manifest, &source, &lists);
+        for ((file, words), _) in FILES.iter().zip(&fixed).zip(&missing).filter(|(_, &missing)| !missing) {
+            let content: String = words.iter().map(|word| format!("{}\n", word)).collect();
+            source.write(file, &content).map_err(|e| LoadError::UnwritableFile {
+                path: source.path_of(file),
+                reason: e.to_string(),
+            })?;
+        }
+
+        let mut fixed_report = check(&manifest, &source, &fixed);
+        fixed_report.fixed = Some(report.issues.len().saturating_sub(fixed_report.issues.len()));
+        Ok(fixed_report)
+    }
+}
+
+fn check(manifest: &Manifest, source: &LanguageSource, lists: &[Vec<String>]) -> LintReport {
+    let tokenizer = manifest.tokenizer();
+    let casing = manifest.casing();
+    let alphabet = allowed_letters(manifest, &tokenizer);
+    let form = majority_form(lists);
+    let mut issues = vec![];
+
+    // Find the words that can't be loaded at all, keeping the rest
+    // as the loader would see them, along with where they are
+    let mut entries = vec![];
+    for (file, lines) in lists.iter().enumerate() {
+        let path = source.path_of(FILES[file]);
+        for (i, line) in lines.iter().enumerate() {
+            let word = line.trim();
+            if word.is_empty() {
+                continue;
+            }
+
+            let mut issue = |kind, message| issues.push(LintIssue {
+                kind, path: path.clone(), line: Some(i + 1), message, file, word: word.to_owned(),
+            });
+
+            if let Some(form) = form {
+                if !form.matches(word) {
+                    issue(IssueKind::MixedNormalization, format!("\"{}\" is not in {}, unlike most of the words", word, form.name()));
+                }
+            }
+
+            let loaded = tokenizer.decompose(&casing.lowercase(word));
+            let tiles = tokenizer.split(&loaded);
+            if tiles.len() != manifest.word_length {
+                issue(IssueKind::WrongLength, format!("\"{}\" has {} letters instead of {}", word, tiles.len(), manifest.word_length));
+            } else if let Some(tile) = alphabet.as_ref().and_then(|alphabet| tiles.iter().find(|tile| !alphabet.contains(*tile))) {
+                issue(IssueKind::OutsideAlphabet, format!("\"{}\" contains '{}', which is not in the alphabet", word, tile));
+            } else {
+                entries.push((file, i + 1, word, loaded));
+            }
+        }
+    }
+
+    // Build the same alphabet that the loader would
+    let mut translator = CharTranslator::new(tokenizer, casing, &manifest.equivalences);
+    if let Some(alphabet) = &manifest.alphabet {
+        translator.update(std::slice::from_ref(alphabet));
+    }
+    for (_, _, _, loaded) in &entries {
+        translator.update(std::slice::from_ref(loaded));
+    }
+
+    // Look for words that are repeated in the same file
+    let mut lists_words: Vec<FxHashMap<Word, usize>> = vec![FxHashMap::default(); FILES.len()];
+    let mut unique_entries = vec![];
+    for &(file, line, word, ref loaded) in &entries {
+        match lists_words[file].entry(Word::from_str(loaded, &translator)) {
+            Entry::Occupied(e) => issues.push(LintIssue {
+                kind: IssueKind::Duplicate,
+                path: source.path_of(FILES[file]),
+                line: Some(line),
+                message: format!("\"{}\" is a duplicate of line {}", word, e.get()),
+                file,
+                word: word.to_owned(),
+            }),
+            Entry::Vacant(e) => {
+                unique_entries.push((file, line, word, *e.key()));
+                e.insert(line);
+            },
+        }
+    }
+
+    // Look for answers that are missing from an otherwise complete valid.txt
+    let (valid, solutions) = (&lists_words[0], &lists_words[1]);
+    let missing: Vec<_> = unique_entries.iter()
+        .filter(|&&(file, _, _, word)| file == 1 && !valid.contains_key(&word))
+        .collect();
+    if lacks_answers(missing.len(), solutions.len()) {
+        for &&(_, _, word, _) in &missing {
+            issues.push(LintIssue {
+                kind: IssueKind::MissingAnswer,
+                path: source.path_of(FILES[0]),
+                line: None,
+                message: format!("\"{}\" is an answer, but it's missing", word),
+                file: 0,
+                word: word.to_owned(),
+            });
+        }
+    }
+
+    // Without an alphabet, look for letters that are rare or aren't
+    // letters at all, which are usually typos or leftovers from the
+    // source that the words were taken from
+    if alphabet.is_none() {
+        let mut letter_words: Vec<Vec<&str>> = vec![vec![]; translator.count()];
+        let mut first_seen = vec![None; translator.count()];
+        let mut counted = FxHashSet::default();
+        for &(file, line, word, ref folded) in &unique_entries {
+            // Words that are in both lists are only counted once
+            if !counted.insert(*folded) {
+                continue;
+            }
+
+            let mut letters = folded.letters().to_vec();
+            letters.sort_unstable();
+            letters.dedup();
+            for idx in letters {
+                letter_words[idx as usize].push(word);
+                first_seen[idx as usize].get_or_insert((file, line));
+            }
+        }
+
+        for (idx, words) in letter_words.iter().enumerate() {
+            let letter = translator.index_to_char(idx as u16);
+            let is_letter = letter.chars().all(|ch| ch.is_alphabetic() || is_combining_mark(ch));
+            let (file, line) = match first_seen[idx] {
+                Some(seen) => seen,
+                None => continue,
+            };
+
+            let used_in = if words.len() < RARE_LETTER_WORDS { words.join(", ") } else { format!("{} words", words.len()) };
+            let message = if !is_letter {
+                format!("'{}' is not a letter, but it appears in {}", letter, used_in)
+            } else if words.len() < RARE_LETTER_WORDS {
+                format!("'{}' only appears in {}", letter, used_in)
+            } else {
+                continue;
+            };
+
+            issues.push(LintIssue {
+                kind: IssueKind::UnusualLetter,
+                path: source.path_of(FILES[file]),
+                line: Some(line),
+                message,
+                file,
+                word: words[0].to_owned(),
+            });
+        }
+    }
+
+    let n_guesses = valid.keys().chain(solutions.keys().filter(|word| !valid.contains_key(word))).count();
+    LintReport {
+        issues,
+        fixed: None,
+        alphabet_size: translator.count(),
+        n_valid: valid.len(),
+        n_solutions: solutions.len(),
+        n_guesses,
+    }
+}
+
+// Rewrites the word lists without the issues that can be fixed. The
+// unusual letters are left alone, since they can also be legitimate.
+fn fix_lists(manifest: &Manifest, source: &LanguageSource, lists: &[Vec<String>]) -> Vec<Vec<String>> {
+    let casing = manifest.casing();
+    let form = majority_form(lists).unwrap_or(Form::Nfc);
+    let normalized: Vec<Vec<String>> = lists.iter()
+        .map(|lines| lines.iter()
+            .map(|line| casing.lowercase(&form.normalize(line.trim())))
+            .collect())
+        .collect();
+
+    // Check the normalized lists to find what should be removed or added
+    let report = check(manifest, source, &normalized);
+    let mut fixed: Vec<Vec<String>> = normalized.iter().enumerate()
+        .map(|(file, lines)| lines.iter().enumerate()
+            .filter(|(i, line)| !line.is_empty() && !report.issues.iter().any(|issue| {
+                let removed = matches!(issue.kind, IssueKind::WrongLength | IssueKind::OutsideAlphabet | IssueKind::Duplicate);
+                removed && issue.file == file && issue.line == Some(i + 1)
+            }))
+            .map(|(_, line)| line.clone())
+            .collect())
+        .collect();
+
+    for issue in report.issues.iter().filter(|issue| issue.kind == IssueKind::MissingAnswer) {
+        fixed[issue.file].push(issue.word.clone());
+    }
+
+    fixed
+}
+
+// The normalization form used by most of the words, if the words don't
+// all use the same one. Words that are the same in both don't count.
+fn majority_form(lists: &[Vec<String>]) -> Option<Form> {
+    let words = lists.iter().flatten().map(|line| line.trim());
+    let (mut nfc, mut nfd, mut neither) = (0, 0, 0);
+    for word in words {
+        match (is_nfc(word), is_nfd(word)) {
+            (true, false) => nfc += 1,
+            (false, true) => nfd += 1,
+            (false, false) => neither += 1,
+            (true, true) => {},
+        }
+    }
+
+    if (nfc > 0 && nfd > 0) || neither > 0 {
+        Some(if nfd > nfc { Form::Nfd } else { Form::Nfc })
+    } else {
+        None
+    }
+}
+
+impl Form {
+    fn matches(self, word: &str) -> bool {
+        match self {
+            Form::Nfc => is_nfc(word),
+            Form::Nfd => is_nfd(word),
+        }
+    }
+
+    fn normalize(self, word: &str) -> String {
+        match self {
+            Form::Nfc => word.nfc().collect(),
+            Form::Nfd => word.nfd().collect(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Form::Nfc => "NFC",
+            Form::Nfd => "NFD",
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use serde::Deserialize;
+
+use crate::common::{DuplicateRule, MAX_WORD_LEN};
+use super::casing::Casing;
+use super::tokenizer::{Decomposition, Tokenizer};
+
+// The optional manifest.toml file in a language's folder, which
+// describes the Wordle that the word lists come from. Every field
+// has a default, which corresponds to the original English Wordle.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    // Name of the game, e.g. "Wordle (ES)"
+    pub name: Option<String>,
+    // Where the game can be played
+    pub url: Option<String>,
+    pub word_length: usize,
+    pub max_guesses: usize,
+    // All the letters that can appear in the words, if specified
+    pub alphabet: Option<String>,
+    // Tiles made of several letters, like "ll", which are otherwise
+    // split into grapheme clusters
+    pub tiles: Vec<String>,
+    // How the words are broken down before splitting them into tiles,
+    // e.g. "hangul" to play with jamo instead of syllables
+    pub decomposition: Decomposition,
+    // The locale whose rules are used to change the case of the words
+    pub locale: Option<String>,
+    pub duplicates: DuplicateRule,
+    // Groups of letters that are the same one when coloring the guesses,
+    // like "aá". The first letter of each group stands for all of them.
+    pub equivalences: Vec<String>,
+    // How many of the most frequent words in freq.txt are considered
+    // common, i.e., likely to be the answer. A quarter of the guesses
+    // are common if it's not specified.
+    pub common_words: Option<usize>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            name: None,
+            url: None,
+            word_length: 5,
+            max_guesses: 6,
+            alphabet: None,
+            tiles: vec![],
+            decomposition: Decomposition::None,
+            locale: None,
+            duplicates: DuplicateRule::Standard,
+            equivalences: vec![],
+            common_words: None,
+        }
+    }
+}
+
+impl Manifest {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let manifest: Self = toml::from_str(content).map_err(|e| e.to_string())?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.word_length == 0 || self.word_length > MAX_WORD_LEN {
+            return Err(format!("word_length must be between 1 and {}", MAX_WORD_LEN));
+        }
+
+        if self.max_guesses == 0 {
+            return Err("max_guesses must be at least 1".to_owned());
+        }
+
+        if self.common_words == Some(0) {
+            return Err("common_words must be at least 1".to_owned());
+        }
+
+        if self.tiles.iter().any(|tile| tile.is_empty() || tile.contains(char::is_whitespace)) {
+            return Err("tiles must not be empty or contain whitespace".to_owned());
+        }
+
+        let tokenizer = self.tokenizer();
+        if let Some(alphabet) = &self.alphabet {
+            let mut letters = tokenizer.split(alphabet);
+            let n_letters = letters.len();
+            letters.sort_unstable();
+            letters.dedup();
+
+            if n_letters == 0 {
+                return Err("alphabet must not be empty".to_owned());
+            } else if letters.len() != n_letters {
+                return Err("alphabet must not contain repeated letters".to_owned());
+            } else if alphabet.contains(char::is_whitespace) {
+                return Err("alphabet must not contain whitespace".to_owned());
+            }
+        }
+
+        let mut seen = vec![];
+        for class in &self.equivalences {
+            let letters = tokenizer.split(class);
+            if letters.len() < 2 {
+                return Err(format!("equivalence \"{}\" must contain at least two letters", class));
+            } else if class.contains(char::is_whitespace) {
+                return Err("equivalences must not contain whitespace".to_owned());
+            }
+
+            for letter in letters {
+                if seen.contains(&letter) {
+                    return Err(format!("'{}' appears more than once in the equivalences", letter));
+                }
+                seen.push(letter);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Splits words into tiles using the language's multi-letter tiles
+    pub fn tokenizer(&self) -> Tokenizer {
+        Tokenizer::new(&self.tiles, self.decomposition)
+    }
+
+    // The rules used to change the case of the words
+    pub fn casing(&self) -> Casing {
+        Casing::from_locale(self.locale.as_deref())
+    }
+
+    // The name of the game, falling back to the language's code
+    pub fn display_name(&self, lang: &str) -> String {
+        self.name.clone().unwrap_or_else(|| format!("Wordle ({})", lang.to_uppercase()))
+    }
+}
@@ -0,0 +1,537 @@
+mod casing;
+mod corpus;
+mod date;
+mod error;
+mod hangul;
+mod import;
+mod lint;
+mod manifest;
+mod overlay;
+mod priors;
+mod sources;
+mod tokenizer;
+mod used;
+
+use std::collections::hash_map::Entry;
+use std::io::ErrorKind;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{Word, MAX_WORD_LEN};
+use casing::Casing;
+use sources::{DataDirs, LanguageSource};
+
+pub use corpus::{CorpusOptions, CorpusReport};
+pub use date::Date;
+pub use error::LoadError;
+pub use import::ImportReport;
+pub use lint::LintReport;
+pub use manifest::Manifest;
+pub use overlay::OverlayEdit;
+pub use priors::Priors;
+pub use tokenizer::Tokenizer;
+
+pub struct DataLoader {
+    dirs: DataDirs,
+    // The casing locale to use instead of the one in the manifests
+    locale: Option<String>,
+    // The day that is being played, if the answers that were used
+    // before it must be left out of the solutions
+    used_before: Option<Date>,
+    lists: ListOverrides,
+}
+
+// The word lists to use instead of the ones in the language's folder
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ListOverrides {
+    pub guesses: Option<PathBuf>,
+    pub solutions: Option<PathBuf>,
+    // Whether the words in the files are added to the language's lists
+    // instead of replacing them
+    pub extend: bool,
+    // The name of the version of the answers to use, which are read
+    // from solutions.{variant}.txt instead of solutions.txt
+    pub variant: Option<String>,
+}
+
+// The files that the lists of a language were read from, including the
+// ones that changed them, to know which lists some results come from
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListFiles {
+    pub guesses: Vec<String>,
+    pub solutions: Vec<String>,
+    // The day before which the used answers were left out, if some were,
+    // since the solutions depend on it as much as on the files
+    #[serde(default)]
+    pub used_before: Option<Date>,
+}
+
+// Everything that is known about a language once it's loaded
+pub struct Language {
+    pub guesses: Vec<Word>,
+    pub solutions: Vec<Word>,
+    pub translator: CharTranslator,
+    pub manifest: Manifest,
+    // How likely every word is to be the answer, if the language has
+    // word frequencies. Otherwise, all the solutions are equally likely.
+    pub priors: Option<Priors>,
+    pub files: ListFiles,
+}
+
+// When loading a language's data, we transform the tiles in the word
+// to numbers, starting from 0 and increasing for every new tile that
+// is found. A tile is usually a single character, but it can also be
+// made of several code points, see `Tokenizer`. This allows the word's
+// characters to be used as indices for the arrays that are sometimes
+// used for efficiency and normalizes all alphabets in the [0-N) range
+// instead of relying on the encoding used by char, which can contain
+// large gaps.
+// This struct keeps track of the mapping from characters to indices, to
+// be able to "translate" words back to be displayed to the user.
+// Letters can also be grouped in equivalence classes, in which case all
+// of them get the same index, and are the same letter for the game.
+pub struct CharTranslator {
+    char_to_index: FxHashMap<String, u16>,
+    index_to_char: Vec<String>,
+    tokenizer: Tokenizer,
+    casing: Casing,
+    // The letter that stands for each letter in an equivalence class
+    folded: FxHashMap<String, String>,
+    // The original spelling of the words that contain folded letters,
+    // since it can't be recovered from the indices
+    spellings: FxHashMap<Word, String>,
+}
+
+impl DataLoader {
+    // Creates a loader that looks up the languages in the default
+    // locations, giving priority to the provided data folder, if any,
+    // optionally overrides the casing locale of every language, leaves
+    // out the answers that were used before the given day, and reads the
+    // lists from other files if requested
+    pub fn new(data_dir: Option<&Path>, locale: Option<String>, used_before: Option<Date>, lists: ListOverrides) -> Self {
+        Self { dirs: DataDirs::new(data_dir), locale, used_before, lists }
+    }
+
+    pub fn load_language(&self, lang: &str) -> Result<Language, LoadError> {
+        // Find where the language's files are and load them
+        let source = self.find_language(lang)?;
+        self.load_source(&source, self.used_before, &self.lists)
+    }
+
+    fn load_source(&self, source: &LanguageSource, used_before: Option<Date>, lists: &ListOverrides) -> Result<Language, LoadError> {
+        let manifest = self.read_manifest(source)?;
+
+        // The list of answers is optional if there are word frequencies,
+        // in which case any valid word can be the answer
+        let has_freqs = source.exists("freq.txt");
+        let solutions_file = match &lists.variant {
+            Some(variant) => variant_file(source, variant)?,
+            None => "solutions.txt".to_owned(),
+        };
+        let mut files = ListFiles::default();
+        let mut valid_words_str = read_list(source, "valid.txt", lists.guesses.as_deref(), lists.extend, &manifest, &mut files.guesses)?;
+        let guesses_path = files.guesses.join(", ");
+        let mut solutions_str = if has_freqs && !source.exists(&solutions_file) && (lists.solutions.is_none() || lists.extend) {
+            files.solutions = files.guesses.clone();
+            let mut words = valid_words_str.clone();
+            if let Some(other) = &lists.solutions {
+                words.extend(read_list(source, &solutions_file, Some(other), false, &manifest, &mut files.solutions)?);
+            }
+            words
+        } else {
+            read_list(source, &solutions_file, lists.solutions.as_deref(), lists.extend, &manifest, &mut files.solutions)?
+        };
+
+        // The game still accepts the usual answers as guesses when other
+        // answers are used, and they may not be in the valid words
+        let default_solutions = source.path_of("solutions.txt");
+        let replaced_guesses = lists.guesses.is_some() && !lists.extend;
+        if !replaced_guesses && !files.solutions.contains(&default_solutions) && source.exists("solutions.txt") {
+            valid_words_str.extend(read_list(source, "solutions.txt", None, false, &manifest, &mut files.guesses)?);
+        }
+
+        // The words that the user added go along with the ones in the
+        // lists, the answers being valid guesses as well
+        let overlay = overlay::read_overlay(source, &manifest)?;
+        valid_words_str.extend(overlay.added_guesses.iter().chain(&overlay.added_answers).cloned());
+        solutions_str.extend(overlay.added_answers.iter().cloned());
+        if !overlay.is_empty() {
+            files.guesses.push(source.path_of(overlay::OVERLAY_FILE));
+            files.solutions.push(source.path_of(overlay::OVERLAY_FILE));
+        }
+
+        let mut translator = build_translator(&manifest, &valid_words_str, &solutions_str);
+
+        // The list of valid words may or may not contain the solutions,
+        // but if it contains most of them and not all, it's probably
+        // incomplete. A few shared words are fine in separate lists.
+        check_answers_in_guesses(&valid_words_str, &solutions_str, &translator, &guesses_path)?;
+
+        // Remember how the words with folded letters are spelled. The
+        // solutions go last, so that their spelling is the one shown
+        // when a guess and a solution only differ in those letters.
+        translator.add_spellings(&valid_words_str);
+        translator.add_spellings(&solutions_str);
+
+        // Create the lists of valid guesses and solutions
+        let mut valid_words = read_words(&valid_words_str, &translator);
+        let mut solutions = read_words(&solutions_str, &translator);
+
+        // Solutions that only differ in equivalent letters are the same
+        // one for the game, so only the first one of them is kept
+        let mut seen = FxHashSet::default();
+        solutions.retain(|word| seen.insert(*word));
+
+        // Extend the list of valid guesses with the solutions
+        valid_words.extend(solutions.iter().copied());
+
+        // Make sure that the list of valid words doesn't contain duplicates.
+        // In some wordles, they sometimes also contain the solutions, so
+        // the previous step may have duplicated them
+        valid_words.sort_unstable();
+        valid_words.dedup();
+
+        // Leave out the words that the user removed. A word that isn't a
+        // valid guess can't be the answer either.
+        let parse_all = |words: &[String]| words.iter().filter_map(|word| translator.parse(word)).collect::<FxHashSet<Word>>();
+        let (removed_guesses, removed_answers) = (parse_all(&overlay.removed_guesses), parse_all(&overlay.removed_answers));
+        valid_words.retain(|word| !removed_guesses.contains(word));
+        solutions.retain(|word| !removed_guesses.contains(word) && !removed_answers.contains(word));
+
+        // Daily Wordles don't repeat their answers, so the ones that were
+        // already used can be left out, although they are still valid
+        if let Some(day) = used_before {
+            let used = used::used_answers(source, &translator, &solutions, day)?;
+            if used.len() < solutions.len() {
+                solutions.retain(|word| !used.contains(word));
+                if !used.is_empty() {
+                    files.solutions.extend(used::USED_FILES.iter().filter(|file| source.exists(file)).map(|file| source.path_of(file)));
+                    files.used_before = Some(day);
+                }
+            } else {
+                eprintln!("Warning: all the answers in {} were already used, so none are left out", source.path_of(&solutions_file));
+            }
+        }
+
+        let priors = if has_freqs {
+            Some(read_priors(source, &manifest, &translator, &valid_words)?)
+        } else {
+            None
+        };
+
+        Ok(Language { guesses: valid_words, solutions, translator, manifest, priors, files })
+    }
+
+    fn find_language(&self, lang: &str) -> Result<LanguageSource, LoadError> {
+        let lang = lang.to_lowercase();
+        self.dirs.find_language(&lang).ok_or_else(|| LoadError::MissingLanguage {
+            lang: lang.clone(),
+            available: self.dirs.available_languages(),
+        })
+    }
+
+    // Reads the language's manifest, or uses the default one if there is
+    // none. The letters in it are turned to lowercase, like the words.
+    fn read_manifest(&self, source: &LanguageSource) -> Result<Manifest, LoadError> {
+        let path = source.path_of("manifest.toml");
+        let mut manifest = match source.read("manifest.toml") {
+            Ok(content) => Manifest::parse(&content)
+                .map_err(|reason| LoadError::BadManifest { path, reason })?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Manifest::default(),
+            Err(e) => return Err(LoadError::UnreadableFile { path, reason: e.to_string() }),
+        };
+
+        if let Some(locale) = &self.locale {
+            manifest.locale = Some(locale.clone());
+        }
+
+        let casing = manifest.casing();
+        manifest.alphabet = manifest.alphabet.map(|alphabet| casing.lowercase(&alphabet));
+        manifest.tiles = manifest.tiles.iter().map(|tile| casing.lowercase(tile)).collect();
+        manifest.equivalences = manifest.equivalences.iter().map(|class| casing.lowercase(class)).collect();
+        Ok(manifest)
+    }
+}
+
+impl CharTranslator {
+    // Creates an empty translator with the given equivalence classes,
+    // where the first letter of each class stands for the others
+    pub fn new(tokenizer: Tokenizer, casing: Casing, equivalences: &[String]) -> Self {
+        let char_to_index = FxHashMap::default();
+        let index_to_char = vec![];
+        let folded = equivalences.iter()
+            .flat_map(|class| {
+                let letters = tokenizer.split(class);
+                let first = letters[0].to_owned();
+                letters.into_iter().map(move |tile| (tile.to_owned(), first.clone()))
+            })
+            .collect();
+        let spellings = FxHashMap::default();
+        Self { char_to_index, index_to_char, tokenizer, casing, folded, spellings }
+    }
+
+    // Updates the translator with the tiles in a list of words,
+    // which are expected to have been checked by `parse_word_list`
+    pub fn update(&mut self, words: &[String]) {
+        for word in words {
+            for tile in self.tokenizer.split(word) {
+                if self.char_to_index.contains_key(tile) {
+                    continue;
+                }
+
+                // Letters in an equivalence class share the index of the
+                // letter that stands for them, which is added if needed
+                let first = self.fold(tile).to_owned();
+                let idx = match self.char_to_index.entry(first.clone()) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        // The index for this tile will be the list's current
+                        // length, which is an index that has not been assigned yet
+                        let idx = self.index_to_char.len() as u16;
+                        e.insert(idx);
+                        self.index_to_char.push(first);
+                        idx
+                    }
+                };
+                self.char_to_index.insert(tile.to_owned(), idx);
+            }
+        }
+    }
+
+    // Remembers the spelling of the words that contain folded letters,
+    // replacing the spelling of any equivalent word that was added before
+    pub fn add_spellings(&mut self, words: &[String]) {
+        for word in words {
+            if self.tiles(word).into_iter().any(|tile| self.fold(tile) != tile) {
+                self.spellings.insert(Word::from_str(word, self), word.clone());
+            }
+        }
+    }
+
+    // The original spelling of a word, if it contains folded letters
+    pub fn spelling(&self, word: &Word) -> Option<&str> {
+        self.spellings.get(word).map(String::as_str)
+    }
+
+    // Gets the letter that stands for a tile in its equivalence class
+    fn fold<'a>(&'a self, tile: &'a str) -> &'a str {
+        self.folded.get(tile).map_or(tile, String::as_str)
+    }
+
+    // Splits a word into its tiles
+    pub fn tiles<'a>(&self, word: &'a str) -> Vec<&'a str> {
+        self.tokenizer.split(word)
+    }
+
+    // Turns a word provided by the user into the form used in the game
+    pub fn normalize(&self, word: &str) -> String {
+        self.tokenizer.decompose(&self.casing.lowercase(word))
+    }
+
+    // Turns a tile to uppercase to display it
+    pub fn uppercase(&self, tile: &str) -> String {
+        self.casing.uppercase(tile)
+    }
+
+    // Shows the tiles of a word as it's usually written
+    pub fn compose(&self, tiles: &[String]) -> String {
+        self.tokenizer.compose(tiles)
+    }
+
+    // Turns a word into the one used in the game, as long as all of its
+    // tiles are known
+    pub fn parse(&self, word: &str) -> Option<Word> {
+        let word = self.normalize(word);
+        let tiles = self.tiles(&word);
+        let known = !tiles.is_empty() && tiles.len() <= MAX_WORD_LEN && tiles.iter().all(|tile| self.has_char(tile));
+        known.then(|| Word::from_str(&word, self))
+    }
+
+    // Gets the tile for an index, assuming it exists
+    pub fn index_to_char(&self, idx: u16) -> &str {
+        &self.index_to_char[idx as usize]
+    }
+
+    // Gets the index for a tile, assuming it exists
+    pub fn char_to_index(&self, tile: &str) -> u16 {
+        self.char_to_index[tile]
+    }
+
+    pub fn has_char(&self, tile: &str) -> bool {
+        self.char_to_index.contains_key(tile)
+    }
+
+    // The number of different indices, i.e., of different letters
+    pub fn count(&self) -> usize {
+        self.index_to_char.len()
+    }
+}
+
+// Creates the character translator for the words found in both lists.
+// If the manifest specifies the alphabet, its letters come first in the
+// translator, in the same order.
+fn build_translator(manifest: &Manifest, guesses: &[String], solutions: &[String]) -> CharTranslator {
+    let mut translator = CharTranslator::new(manifest.tokenizer(), manifest.casing(), &manifest.equivalences);
+    if let Some(alphabet) = &manifest.alphabet {
+        translator.update(std::slice::from_ref(alphabet));
+    }
+    translator.update(guesses);
+    translator.update(solutions);
+    translator
+}
+
+pub fn read_words(words: &[String], translator: &CharTranslator) -> Vec<Word> {
+    words.iter().map(|word| Word::from_str(word, translator)).collect()
+}
+
+// Reads one of the word lists of a language, or the file that replaces
+// it, or both if the file extends it, keeping track of the files read
+fn read_list(source: &LanguageSource, file: &str, other: Option<&Path>, extend: bool, manifest: &Manifest, files: &mut Vec<String>) -> Result<Vec<String>, LoadError> {
+    let mut words = vec![];
+    if other.is_none() || extend {
+        let path = source.path_of(file);
+        let content = source.read(file).map_err(|e| LoadError::UnreadableFile { path: path.clone(), reason: e.to_string() })?;
+        words.extend(parse_word_list(&content, &path, manifest)?);
+        files.push(path);
+    }
+
+    if let Some(other) = other {
+        let path = other.display().to_string();
+        let content = read_to_string(other).map_err(|e| LoadError::UnreadableFile { path: path.clone(), reason: e.to_string() })?;
+        words.extend(parse_word_list(&content, &path, manifest)?);
+        files.push(path);
+    }
+
+    Ok(words)
+}
+
+// The file with a version of the answers of a language, which must exist
+fn variant_file(source: &LanguageSource, variant: &str) -> Result<String, LoadError> {
+    let file = format!("solutions.{}.txt", variant);
+    if source.exists(&file) {
+        return Ok(file);
+    }
+
+    let mut available: Vec<String> = source.files().into_iter()
+        .filter_map(|name| Some(name.strip_prefix("solutions.")?.strip_suffix(".txt")?.to_owned()))
+        .collect();
+    available.sort_unstable();
+    Err(LoadError::MissingVariant { path: source.path_of(&file), available })
+}
+
+// Parses a word list, with one word per line. Blank lines and whitespace
+// around the words are ignored, and all the words are turned to
+// lowercase for consistency. Every word is expected to have as many
+// tiles as the length set in the manifest, and only use letters from
+// its alphabet or equivalences if it has one.
+fn parse_word_list(content: &str, path: &str, manifest: &Manifest) -> Result<Vec<String>, LoadError> {
+    let alphabet = allowed_letters(manifest, &manifest.tokenizer());
+
+    let mut words = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let word = line.trim();
+        if word.is_empty() {
+            continue;
+        }
+
+        let word = check_word(word, manifest, alphabet.as_ref())
+            .map_err(|reason| LoadError::BadWord { path: path.to_owned(), line: i + 1, word: word.to_owned(), reason })?;
+        words.push(word);
+    }
+
+    if words.is_empty() {
+        return Err(LoadError::EmptyList { path: path.to_owned() });
+    }
+
+    Ok(words)
+}
+
+// Turns a word into the form used in the game, as long as it has as
+// many tiles as the length set in the manifest, and only uses letters
+// from its alphabet or equivalences if it has one
+fn check_word(word: &str, manifest: &Manifest, alphabet: Option<&FxHashSet<&str>>) -> Result<String, String> {
+    let tokenizer = manifest.tokenizer();
+    let word = tokenizer.decompose(&manifest.casing().lowercase(word));
+    let tiles = tokenizer.split(&word);
+    if tiles.len() != manifest.word_length {
+        return Err(format!("has {} letters instead of {}", tiles.len(), manifest.word_length));
+    }
+
+    if let Some(alphabet) = alphabet {
+        if let Some(tile) = tiles.iter().find(|tile| !alphabet.contains(*tile)) {
+            return Err(format!("contains '{}', which is not in the alphabet", tile));
+        }
+    }
+
+    Ok(word)
+}
+
+// Reads the word frequencies of a language, with one word per line from
+// the most to the least frequent, optionally followed by the number of
+// times that it appears. Words that aren't valid guesses are ignored,
+// so any frequency list for the language can be used, and the guesses
+// that aren't in it are ranked last.
+fn read_priors(source: &LanguageSource, manifest: &Manifest, translator: &CharTranslator, guesses: &[Word]) -> Result<Priors, LoadError> {
+    let content = source.read("freq.txt").map_err(|e| LoadError::UnreadableFile {
+        path: source.path_of("freq.txt"),
+        reason: e.to_string(),
+    })?;
+
+    let mut unranked: FxHashSet<Word> = guesses.iter().copied().collect();
+    let mut ranking = Vec::with_capacity(guesses.len());
+    for line in content.lines() {
+        let word = match line.split_whitespace().next() {
+            Some(word) => translator.normalize(word),
+            None => continue,
+        };
+
+        let tiles = translator.tiles(&word);
+        if tiles.len() != manifest.word_length || !tiles.iter().all(|tile| translator.has_char(tile)) {
+            continue;
+        }
+
+        let word = Word::from_str(&word, translator);
+        if unranked.remove(&word) {
+            ranking.push(word);
+        }
+    }
+
+    ranking.extend(guesses.iter().filter(|word| unranked.contains(word)));
+    let n_common = manifest.common_words.unwrap_or(ranking.len() / 4).max(1);
+    Ok(Priors::from_ranking(&ranking, n_common))
+}
+
+// The letters that the words can use, if the manifest has an alphabet.
+// The letters in the equivalences are also part of the alphabet.
+fn allowed_letters<'a>(manifest: &'a Manifest, tokenizer: &Tokenizer) -> Option<FxHashSet<&'a str>> {
+    manifest.alphabet.as_ref().map(|alphabet| {
+        manifest.equivalences.iter()
+            .flat_map(|class| tokenizer.split(class))
+            .chain(tokenizer.split(alphabet))
+            .collect()
+    })
+}
+
+// Words are compared once their letters are folded, so the answers can
+// be spelled differently in the list of valid words
+fn check_answers_in_guesses(guesses: &[String], solutions: &[String], translator: &CharTranslator, path: &str) -> Result<(), LoadError> {
+    let guesses: FxHashSet<Word> = read_words(guesses, translator).into_iter().collect();
+    let missing: Vec<String> = solutions.iter()
+        .filter(|word| !guesses.contains(&Word::from_str(word, translator)))
+        .cloned()
+        .collect();
+
+    if lacks_answers(missing.len(), solutions.len()) {
+        Err(LoadError::MissingAnswers { path: path.to_owned(), words: missing })
+    } else {
+        Ok(())
+    }
+}
+
+// Whether the list of valid words is probably incomplete, because it
+// contains most of the solutions, but not all of them
+fn lacks_answers(n_missing: usize, n_solutions: usize) -> bool {
+    n_missing != 0 && 2 * n_missing <= n_solutions
+}
@@ -0,0 +1,119 @@
+use std::io::ErrorKind;
+
+use super::sources::LanguageSource;
+use super::{check_word, allowed_letters, DataLoader, LoadError, Manifest};
+
+// The user's own changes to the word lists of a language, with one
+// change per line, like "+guess word" or "-answer word"
+pub(super) const OVERLAY_FILE: &str = "overlay.txt";
+
+// A change to the word lists that the user can make
+#[derive(Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum OverlayEdit {
+    /// A word that the game accepts as a guess
+    AddGuess,
+    /// A word that the game doesn't accept as a guess, nor as an answer
+    RemoveGuess,
+    /// A word that can be the answer, and is also a valid guess
+    AddAnswer,
+    /// A word that is a valid guess, but can't be the answer
+    RemoveAnswer,
+}
+
+// The changes in a language's overlay, with the words in the form
+// that is used in the game
+#[derive(Default)]
+pub struct Overlay {
+    pub added_guesses: Vec<String>,
+    pub removed_guesses: Vec<String>,
+    pub added_answers: Vec<String>,
+    pub removed_answers: Vec<String>,
+}
+
+impl OverlayEdit {
+    fn prefix(self) -> &'static str {
+        match self {
+            OverlayEdit::AddGuess => "+guess",
+            OverlayEdit::RemoveGuess => "-guess",
+            OverlayEdit::AddAnswer => "+answer",
+            OverlayEdit::RemoveAnswer => "-answer",
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        [OverlayEdit::AddGuess, OverlayEdit::RemoveGuess, OverlayEdit::AddAnswer, OverlayEdit::RemoveAnswer]
+            .into_iter()
+            .find(|edit| edit.prefix() == prefix)
+    }
+}
+
+impl Overlay {
+    pub fn is_empty(&self) -> bool {
+        self.added_guesses.is_empty() && self.removed_guesses.is_empty()
+            && self.added_answers.is_empty() && self.removed_answers.is_empty()
+    }
+}
+
+impl DataLoader {
+    // Adds a change to the overlay of a language, making sure that the
+    // word could be in its lists
+    pub fn edit_overlay(&self, lang: &str, edit: OverlayEdit, word: &str) -> Result<(), LoadError> {
+        let source = self.find_language(lang)?;
+        let manifest = self.read_manifest(&source)?;
+        let path = source.path_of(OVERLAY_FILE);
+        let word = manifest.casing().lowercase(word.trim());
+        let alphabet = allowed_letters(&manifest, &manifest.tokenizer());
+        if let Err(reason) = check_word(&word, &manifest, alphabet.as_ref()) {
+            return Err(LoadError::InvalidWord { word, reason });
+        }
+
+        let mut content = match source.read(OVERLAY_FILE) {
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            content => content.map_err(|e| LoadError::UnreadableFile { path: path.clone(), reason: e.to_string() })?,
+        };
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!("{} {}\n", edit.prefix(), word));
+
+        source.write(OVERLAY_FILE, &content)
+            .map_err(|e| LoadError::UnwritableFile { path, reason: e.to_string() })
+    }
+}
+
+// Reads the overlay of a language, if it has one. Blank lines and the
+// ones that start with # are ignored, and the words must be valid for
+// the language, just like the ones in the word lists.
+pub fn read_overlay(source: &LanguageSource, manifest: &Manifest) -> Result<Overlay, LoadError> {
+    let path = source.path_of(OVERLAY_FILE);
+    let content = match source.read(OVERLAY_FILE) {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Overlay::default()),
+        content => content.map_err(|e| LoadError::UnreadableFile { path: path.clone(), reason: e.to_string() })?,
+    };
+
+    let alphabet = allowed_letters(manifest, &manifest.tokenizer());
+    let mut overlay = Overlay::default();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let bad_line = |word: &str, reason: String| LoadError::BadWord { path: path.clone(), line: i + 1, word: word.to_owned(), reason };
+        let (edit, word) = match line.split_once(char::is_whitespace) {
+            Some((prefix, word)) => (OverlayEdit::from_prefix(prefix), word.trim()),
+            None => (None, line),
+        };
+        let edit = edit.ok_or_else(|| bad_line(line, "must start with +guess, -guess, +answer or -answer, followed by the word".to_owned()))?;
+        let word = check_word(word, manifest, alphabet.as_ref()).map_err(|reason| bad_line(word, reason))?;
+
+        match edit {
+            OverlayEdit::AddGuess => overlay.added_guesses.push(word),
+            OverlayEdit::RemoveGuess => overlay.removed_guesses.push(word),
+            OverlayEdit::AddAnswer => overlay.added_answers.push(word),
+            OverlayEdit::RemoveAnswer => overlay.removed_answers.push(word),
+        }
+    }
+
+    Ok(overlay)
+}
@@ -0,0 +1,41 @@
+use rustc_hash::FxHashMap;
+
+use crate::common::Word;
+
+// How far apart the most and the least frequent words are placed in
+// the sigmoid, which sets how quickly the probabilities fall off
+const SIGMOID_WIDTH: f64 = 10.0;
+
+// The prior probability of every guess being the answer, for the
+// Wordles whose list of answers isn't known. It's derived from how
+// frequent the words are: they are ranked from the most to the least
+// frequent and placed along a sigmoid, so the common words are all
+// similarly likely, while the rare ones are almost never the answer.
+pub struct Priors {
+    weights: FxHashMap<Word, f64>,
+}
+
+impl Priors {
+    // Assigns the probabilities to words ranked by their frequency,
+    // with the most frequent one first. Around the first `n_common`
+    // words are more likely than not to be the answer.
+    pub fn from_ranking(ranking: &[Word], n_common: usize) -> Self {
+        let n_words = ranking.len() as f64;
+        let weights = ranking.iter().enumerate()
+            .map(|(rank, word)| {
+                let x = SIGMOID_WIDTH * (n_common as f64 - rank as f64) / n_words;
+                (*word, 1.0 / (1.0 + (-x).exp()))
+            })
+            .collect();
+        Self { weights }
+    }
+
+    pub fn weight(&self, word: &Word) -> f64 {
+        self.weights.get(word).copied().unwrap_or(0.0)
+    }
+
+    // The weights of a list of words, in the same order
+    pub fn weights(&self, words: &[Word]) -> Vec<f64> {
+        words.iter().map(|word| self.weight(word)).collect()
+    }
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::fs::{read_dir, read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::dirs::{env_path, xdg_dir};
+
+#[cfg(feature = "embedded-data")]
+use include_dir::{include_dir, Dir};
+
+// The languages in the data folder, bundled at compile time
+#[cfg(feature = "embedded-data")]
+static EMBEDDED_DATA: Dir = include_dir!("$CARGO_MANIFEST_DIR/data");
+
+// Where the files for a language are read from. A language is always
+// read from a single place, the files are never mixed across folders.
+pub enum LanguageSource {
+    Folder(PathBuf),
+    #[cfg(feature = "embedded-data")]
+    Embedded(&'static Dir<'static>),
+}
+
+// The list of folders where languages are looked up, from highest
+// to lowest priority. In order, these are:
+// - The folder provided through the command line, if any
+// - The folder in the ELDROW_DATA environment variable, if any
+// - The data folder in the current working directory
+// - The eldrow folder inside the XDG data home (~/.local/share)
+// - The eldrow folder inside every XDG data dir (/usr/local/share, /usr/share)
+// The languages that are embedded in the binary, if the feature is
+// enabled, are only used when none of these folders provides them.
+pub struct DataDirs {
+    dirs: Vec<PathBuf>,
+}
+
+impl DataDirs {
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        let mut dirs: Vec<PathBuf> = data_dir.map(Path::to_path_buf).into_iter().collect();
+        dirs.extend(env_path("ELDROW_DATA"));
+        dirs.push(PathBuf::from("data"));
+
+        dirs.extend(xdg_dir("XDG_DATA_HOME", ".local/share"));
+
+        let data_dirs = env::var_os("XDG_DATA_DIRS")
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+        dirs.extend(env::split_paths(&data_dirs).map(|dir| dir.join("eldrow")));
+
+        Self { dirs }
+    }
+
+    // Finds the source with the highest priority that provides a language
+    pub fn find_language(&self, lang: &str) -> Option<LanguageSource> {
+        let folder = self.dirs.iter()
+            .map(|dir| dir.join(lang))
+            .find(|path| path.is_dir())
+            .map(LanguageSource::Folder);
+
+        #[cfg(feature = "embedded-data")]
+        let folder = folder.or_else(|| EMBEDDED_DATA.get_dir(lang).map(LanguageSource::Embedded));
+
+        folder
+    }
+
+    // Lists the names of all the languages that can be found
+    pub fn available_languages(&self) -> Vec<String> {
+        let mut langs: Vec<String> = self.dirs.iter()
+            .filter_map(|dir| read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+
+        #[cfg(feature = "embedded-data")]
+        langs.extend(EMBEDDED_DATA.dirs()
+            .filter_map(|dir| dir.path().to_str())
+            .map(str::to_owned));
+
+        langs.sort_unstable();
+        langs.dedup();
+        langs
+    }
+}
+
+impl LanguageSource {
+    // Reads one of the language's files
+    pub fn read(&self, file: &str) -> io::Result<String> {
+        match self {
+            LanguageSource::Folder(path) => read_to_string(path.join(file)),
+            #[cfg(feature = "embedded-data")]
+            LanguageSource::Embedded(dir) => dir.get_file(dir.path().join(file))
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?
+                .contents_utf8()
+                .map(str::to_owned)
+                .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData)),
+        }
+    }
+
+    pub fn exists(&self, file: &str) -> bool {
+        match self {
+            LanguageSource::Folder(path) => path.join(file).exists(),
+            #[cfg(feature = "embedded-data")]
+            LanguageSource::Embedded(dir) => dir.get_file(dir.path().join(file)).is_some(),
+        }
+    }
+
+    // Lists the names of the language's files
+    pub fn files(&self) -> Vec<String> {
+        match self {
+            LanguageSource::Folder(path) => read_dir(path).into_iter()
+                .flat_map(|entries| entries.flatten())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect(),
+            #[cfg(feature = "embedded-data")]
+            LanguageSource::Embedded(dir) => dir.files()
+                .filter_map(|file| file.path().file_name()?.to_str().map(str::to_owned))
+                .collect(),
+        }
+    }
+
+    // Replaces one of the language's files, which is only possible when
+    // it's read from a folder
+    pub fn write(&self, file: &str, content: &str) -> io::Result<()> {
+        match self {
+            LanguageSource::Folder(path) => write(path.join(file), content),
+            #[cfg(feature = "embedded-data")]
+            LanguageSource::Embedded(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "the language is embedded in the binary")),
+        }
+    }
+
+    // The path to one of the language's files, to be shown in messages
+    pub fn path_of(&self, file: &str) -> String {
+        match self {
+            LanguageSource::Folder(path) => path.join(file).display().to_string(),
+            #[cfg(feature = "embedded-data")]
+            LanguageSource::Embedded(dir) => format!("<embedded>/{}", dir.path().join(file).display()),
+        }
+    }
+}
+
@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::hangul;
+
+// How the words of a language are broken down before splitting them
+#[derive(Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decomposition {
+    // The words are used as they are
+    #[default]
+    None,
+    // Hangul syllables are split into jamo, which are the tiles
+    Hangul,
+}
+
+// Splits words into the tiles that are shown in the game. Every tile is
+// an extended grapheme cluster, i.e., what a reader sees as a single
+// character, even if it's made of several code points, like a letter
+// with a combining accent or a Devanagari conjunct. Some languages also
+// have tiles made of several letters, like "ll", which take priority.
+#[derive(Clone, Default)]
+pub struct Tokenizer {
+    tiles: Vec<String>,
+    decomposition: Decomposition,
+}
+
+impl Tokenizer {
+    pub fn new(tiles: &[String], decomposition: Decomposition) -> Self {
+        // Longer tiles go first, so that "dzs" wins over "dz"
+        let mut tiles = tiles.to_vec();
+        tiles.sort_by_key(|tile| std::cmp::Reverse(tile.len()));
+        Self { tiles, decomposition }
+    }
+
+    // Breaks a word down into the form that is used in the game, which
+    // is the one that must be split into tiles
+    pub fn decompose(&self, word: &str) -> String {
+        match self.decomposition {
+            Decomposition::None => word.to_owned(),
+            Decomposition::Hangul => hangul::decompose(word),
+        }
+    }
+
+    // Joins the tiles of a word to show it as it's usually written
+    pub fn compose(&self, tiles: &[String]) -> String {
+        match self.decomposition {
+            Decomposition::None => tiles.concat(),
+            Decomposition::Hangul => hangul::compose(&tiles.concat()),
+        }
+    }
+
+    pub fn split<'a>(&self, word: &'a str) -> Vec<&'a str> {
+        let mut tiles = vec![];
+        let mut rest = word;
+        while let Some(first) = rest.graphemes(true).next() {
+            let len = self.tiles.iter()
+                .find(|tile| rest.starts_with(tile.as_str()))
+                .map_or(first.len(), String::len);
+            tiles.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+
+        tiles
+    }
+}
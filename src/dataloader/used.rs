@@ -0,0 +1,129 @@
+use std::io::ErrorKind;
+use rustc_hash::FxHashSet;
+
+use crate::common::Word;
+use super::date::Date;
+use super::sources::LanguageSource;
+use super::{CharTranslator, DataLoader, ListOverrides, LoadError};
+
+// The answers that were already used, one per line, optionally
+// followed by the day that they were used
+const USED_FILE: &str = "used.txt";
+
+// The answers of a daily Wordle, with a day and its answer per line
+const SCHEDULE_FILE: &str = "schedule.txt";
+
+// The files that can leave answers out
+pub(super) const USED_FILES: [&str; 2] = [USED_FILE, SCHEDULE_FILE];
+
+impl DataLoader {
+    // Appends the answer for a day to the language's used answers, which
+    // is taken from the schedule if it's not provided. Returns the word
+    // and whether it was added, since it's not added again if it's
+    // already in the list.
+    pub fn record_answer(&self, lang: &str, word: Option<&str>, day: Date) -> Result<(String, bool), LoadError> {
+        let source = self.find_language(lang)?;
+        let language = self.load_source(&source, None, &ListOverrides::default())?;
+        let translator = &language.translator;
+
+        let word = match word {
+            Some(word) => language.manifest.casing().lowercase(word.trim()),
+            None => read_entries(&source, SCHEDULE_FILE, true)?.into_iter()
+                .find(|entry| entry.day == Some(Ok(day)))
+                .map(|entry| entry.word)
+                .ok_or_else(|| LoadError::NoScheduledAnswer { path: source.path_of(SCHEDULE_FILE), day: day.to_string() })?,
+        };
+
+        let answer = translator.parse(&word);
+        if !answer.is_some_and(|answer| language.solutions.contains(&answer)) {
+            return Err(LoadError::NotAnAnswer { word });
+        }
+
+        let entries = read_entries(&source, USED_FILE, false)?;
+        if entries.iter().any(|entry| translator.parse(&entry.word) == answer) {
+            return Ok((word, false));
+        }
+
+        let mut content = read_optional(&source, USED_FILE)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!("{} {}\n", word, day));
+
+        source.write(USED_FILE, &content).map_err(|e| LoadError::UnwritableFile {
+            path: source.path_of(USED_FILE),
+            reason: e.to_string(),
+        })?;
+        Ok((word, true))
+    }
+}
+
+// A line of the used answers or the schedule
+struct Entry {
+    line: usize,
+    word: String,
+    // The day of the answer, if there is one, or the text that
+    // could not be read as a date
+    day: Option<Result<Date, String>>,
+}
+
+// Finds the answers that were used before a day, which are the ones in
+// the list of used answers, unless they are dated on that day or later,
+// and the ones scheduled before it. Any word that isn't an answer is
+// reported, since it's probably a typo or from a different Wordle.
+pub fn used_answers(source: &LanguageSource, translator: &CharTranslator, solutions: &[Word], day: Date) -> Result<FxHashSet<Word>, LoadError> {
+    let answers: FxHashSet<&Word> = solutions.iter().collect();
+    let mut used = FxHashSet::default();
+
+    for (file, needs_day) in [(USED_FILE, false), (SCHEDULE_FILE, true)] {
+        for entry in read_entries(source, file, needs_day)? {
+            let warn = |message: String| eprintln!("Warning: {}, line {}: {}", source.path_of(file), entry.line, message);
+            let entry_day = match entry.day {
+                Some(Ok(entry_day)) => Some(entry_day),
+                Some(Err(text)) => {
+                    warn(format!("\"{}\" is not a valid date, expected YYYY-MM-DD", text));
+                    continue;
+                },
+                None => None,
+            };
+
+            match translator.parse(&entry.word) {
+                Some(word) if answers.contains(&word) => {
+                    if entry_day.is_none_or(|entry_day| entry_day < day) {
+                        used.insert(word);
+                    }
+                },
+                _ => warn(format!("\"{}\" is not one of the answers", entry.word)),
+            }
+        }
+    }
+
+    Ok(used)
+}
+
+// Reads the entries in one of the optional files, where the day goes
+// after the word in the used answers and before it in the schedule
+fn read_entries(source: &LanguageSource, file: &str, day_first: bool) -> Result<Vec<Entry>, LoadError> {
+    let content = read_optional(source, file)?;
+    let entries = content.lines().enumerate()
+        .filter_map(|(i, line)| {
+            let mut fields = line.split_whitespace();
+            let (first, second) = (fields.next()?, fields.next());
+            let (word, day) = if day_first { (second.unwrap_or(""), Some(first)) } else { (first, second) };
+            Some(Entry {
+                line: i + 1,
+                word: word.to_owned(),
+                day: day.map(|day| day.parse().map_err(|_| day.to_owned())),
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+// Reads one of the language's files, which is empty if it doesn't exist
+fn read_optional(source: &LanguageSource, file: &str) -> Result<String, LoadError> {
+    match source.read(file) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        content => content.map_err(|e| LoadError::UnreadableFile { path: source.path_of(file), reason: e.to_string() }),
+    }
+}
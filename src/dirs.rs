@@ -0,0 +1,25 @@
+use std::env;
+use std::path::PathBuf;
+
+// The eldrow folder inside one of the XDG base directories, which is
+// the one in the given environment variable, or the given folder inside
+// the home directory by default, e.g. XDG_STATE_HOME or ~/.local/state
+pub fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    env_path(var)
+        .or_else(|| env_path("HOME").map(|home| home.join(default)))
+        .map(|dir| dir.join("eldrow"))
+}
+
+// One of the files that Eldrow keeps in an XDG base directory, which is
+// in the current folder if there is no home
+pub fn xdg_file(var: &str, default: &str, file: &str) -> PathBuf {
+    match xdg_dir(var, default) {
+        Some(dir) => dir.join(file),
+        None => PathBuf::from(format!("eldrow-{}", file)),
+    }
+}
+
+// Reads a path from an environment variable, ignoring it if it's empty
+pub fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
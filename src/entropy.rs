@@ -1,32 +1,236 @@
-use crate::common::Word;
+use crate::common::{Word, Pattern, DuplicateRule};
+use crate::dataloader::Priors;
+use crate::model::GuessModel;
 
 use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 
-// Finds the word that maximizes expected entropy
-// between the given list of possible solutions
-pub fn find_best_splitter(guesses: &[Word], solutions: &[Word]) -> Word {
-    *guesses.par_iter()
-            .map(|word| (word, expected_entropy(word, solutions)))
-            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-            .unwrap()
-            .0
+// The metrics that can be used to rank how good a guess is
+#[derive(Copy, Clone, clap::ValueEnum)]
+pub enum Metric {
+    /// Expected information gained, in bits
+    Entropy,
+    /// Size of the largest group of solutions that may remain
+    Minimax,
+    /// Expected number of solutions that will remain
+    ExpectedSize,
 }
 
-// Computes the expected entropy for a word given a list of solutions
-fn expected_entropy(guess: &Word, solutions: &[Word]) -> f64 {
-    let mut pattern_count = [0.0; 243];  // The total number of possible
-    let n_sols = solutions.len() as f64; // color patterns is 243, or 3^5
+impl Metric {
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Entropy => "entropy",
+            Metric::Minimax => "minimax",
+            Metric::ExpectedSize => "expected size",
+        }
+    }
+}
+
+// How the guesses are scored when playing, as chosen by the user
+#[derive(Copy, Clone, clap::ValueEnum)]
+pub enum Scoring {
+    /// Expected information gained, in bits
+    Entropy,
+    /// Expected number of guesses until the answer is found
+    ExpectedGuesses,
+}
+
+// The ways of choosing the next guess, given the solutions that remain
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub enum Strategy {
+    // Gain as much information as possible, and guess one of the
+    // solutions once there are only one or two of them left
+    Entropy,
+    // Minimize the expected number of guesses, estimating how many are
+    // needed after gaining some information with a model
+    ExpectedGuesses(GuessModel),
+}
+
+// Picks the next guess according to the strategy
+pub fn choose_guess(guesses: &[Word], solutions: &[Word], priors: Option<&Priors>, rule: DuplicateRule, strategy: Strategy) -> Word {
+    match strategy {
+        // With two solutions, the worst case is already two turns, so
+        // by using one of them, we'll get it right in one turn at least
+        // 50% of the time
+        Strategy::Entropy if solutions.len() <= 2 => most_likely(solutions, priors),
+        _ => choose_from_entropies(&guess_entropies(guesses, solutions, priors, rule), solutions, priors, strategy),
+    }
+}
+
+// Same as `choose_guess`, but with the expected entropy of every guess
+// already computed, so that it can also be shown to the user
+pub fn choose_from_entropies(entropies: &[(Word, f64)], solutions: &[Word], priors: Option<&Priors>, strategy: Strategy) -> Word {
+    match strategy {
+        Strategy::Entropy if solutions.len() <= 2 => most_likely(solutions, priors),
+        Strategy::Entropy => best_splitter(entropies),
+        Strategy::ExpectedGuesses(model) => fastest_guess(entropies, solutions, priors, &model),
+    }
+}
+
+// The expected entropy of every guess, in the same order as the guesses
+pub fn guess_entropies(guesses: &[Word], solutions: &[Word], priors: Option<&Priors>, rule: DuplicateRule) -> Vec<(Word, f64)> {
+    let weights = priors.map(|priors| priors.weights(solutions));
+    guesses.par_iter()
+        .map(|word| (*word, entropy(&count_patterns(word, solutions, weights.as_deref(), rule))))
+        .collect()
+}
+
+// Finds the word that minimizes the expected number of guesses, which
+// is 1 if it's the answer, and otherwise 1 plus the guesses that the
+// model estimates for the uncertainty that will remain after it
+fn fastest_guess(entropies: &[(Word, f64)], solutions: &[Word], priors: Option<&Priors>, model: &GuessModel) -> Word {
+    let weights = priors.map(|priors| priors.weights(solutions));
+    let total = weights.as_ref().map_or(solutions.len() as f64, |weights| weights.iter().sum());
+    let chances: FxHashMap<Word, f64> = solutions.iter().enumerate()
+        .map(|(i, word)| (*word, weights.as_ref().map_or(1.0, |weights| weights[i]) / total))
+        .collect();
+    let bits = uncertainty(solutions, priors);
+
+    entropies.par_iter()
+        .map(|&(word, info)| {
+            let chance = chances.get(&word).copied().unwrap_or(0.0);
+            (word, chance + (1.0 - chance) * (1.0 + model.guesses(bits - info)))
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+        .0
+}
+
+// The uncertainty about the answer, in bits, given the solutions that
+// remain and how likely they are
+pub fn uncertainty(solutions: &[Word], priors: Option<&Priors>) -> f64 {
+    match priors {
+        Some(priors) => entropy(&priors.weights(solutions)),
+        None => (solutions.len() as f64).log2(),
+    }
+}
+
+// Finds the word that maximizes expected entropy between the possible
+// solutions, which are weighted by their prior probabilities if there
+// are any
+fn best_splitter(entropies: &[(Word, f64)]) -> Word {
+    entropies.par_iter()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap()
+        .0
+}
+
+// The best `n` guesses by expected entropy, from the most to the least
+// informative
+pub fn best_by_entropy(entropies: &[(Word, f64)], n: usize) -> Vec<(Word, f64)> {
+    let mut ranking = entropies.to_vec();
+    ranking.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    ranking.truncate(n);
+    ranking
+}
 
-    for sol in solutions {
-        let idx = guess.compute_pattern(sol).to_index();
-        pattern_count[idx] += 1.0;
+// Picks the solution that is most likely to be the answer, which is
+// the first one when all of them are equally likely
+pub fn most_likely(solutions: &[Word], priors: Option<&Priors>) -> Word {
+    match priors {
+        Some(priors) => *solutions.iter()
+            .reduce(|best, word| if priors.weight(word) > priors.weight(best) { word } else { best })
+            .unwrap(),
+        None => solutions[0],
     }
+}
+
+// Ranks all the guesses according to a metric, returning the best
+// `n` of them along with their scores. For the entropy the score is
+// the amount of bits, and for the others it's the number of solutions,
+// so the best score is the highest or the lowest one respectively.
+pub fn rank_words(guesses: &[Word], solutions: &[Word], priors: Option<&Priors>, rule: DuplicateRule, metric: Metric, n: usize) -> Vec<(Word, f64)> {
+    let weights = priors.map(|priors| priors.weights(solutions));
+    let mut ranking: Vec<(Word, f64)> = guesses.par_iter()
+        .map(|word| (*word, score(word, solutions, weights.as_deref(), rule, metric)))
+        .collect();
+
+    match metric {
+        Metric::Entropy => ranking.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap()),
+        Metric::Minimax | Metric::ExpectedSize => ranking.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap()),
+    }
+
+    ranking.truncate(n);
+    ranking
+}
+
+// The largest group is the worst case regardless of how likely the
+// solutions are, so it's the only metric that doesn't use the weights
+fn score(guess: &Word, solutions: &[Word], weights: Option<&[f64]>, rule: DuplicateRule, metric: Metric) -> f64 {
+    match metric {
+        Metric::Entropy => entropy(&count_patterns(guess, solutions, weights, rule)),
+        Metric::Minimax => largest_group(&count_patterns(guess, solutions, None, rule)),
+        Metric::ExpectedSize => expected_size(&count_patterns(guess, solutions, weights, rule)),
+    }
+}
+
+// Computes the expected entropy for a word given a list of solutions
+pub fn expected_entropy(guess: &Word, solutions: &[Word], priors: Option<&Priors>, rule: DuplicateRule) -> f64 {
+    let weights = priors.map(|priors| priors.weights(solutions));
+    entropy(&count_patterns(guess, solutions, weights.as_deref(), rule))
+}
+
+// Computes the entropy of the distribution of patterns, given how many
+// solutions (or how much probability) produce each of them
+fn entropy(pattern_count: &[f64]) -> f64 {
+    let n_sols: f64 = pattern_count.iter().sum();
 
-    -pattern_count.into_iter() // Flip the sign because logs of numbers < 1 are negative
-        .filter(|&x| x > 0.0) // Avoid NaNs when computing log2
+    -pattern_count.iter() // Flip the sign because logs of numbers < 1 are negative
+        .filter(|&&x| x > 0.0) // Avoid NaNs when computing log2
         .map(|count| {
             let p = count / n_sols;
             let e = p.log2();
             p * e
         }).sum::<f64>()
-}
\ No newline at end of file
+}
+
+// Computes the size of the largest group of solutions that
+// share the same pattern, which is the worst case for this guess
+fn largest_group(pattern_count: &[f64]) -> f64 {
+    pattern_count.iter().copied().fold(0.0, f64::max)
+}
+
+// Computes the expected number of solutions that will remain after
+// using this guess. Every group of solutions that share a pattern is
+// as likely as the solutions in it, so each one contributes size^2 / n
+fn expected_size(pattern_count: &[f64]) -> f64 {
+    let n_sols: f64 = pattern_count.iter().sum();
+    pattern_count.iter()
+        .map(|count| count * count / n_sols)
+        .sum()
+}
+
+// Counts how many solutions produce each of the patterns for a guess,
+// or how likely each pattern is if the solutions have weights
+fn count_patterns(guess: &Word, solutions: &[Word], weights: Option<&[f64]>, rule: DuplicateRule) -> Vec<f64> {
+    // Having the length as a constant lets the compiler unroll the loops
+    // when computing the patterns, which is noticeably faster
+    match guess.len {
+        1 => count_patterns_len::<1>(guess, solutions, weights, rule),
+        2 => count_patterns_len::<2>(guess, solutions, weights, rule),
+        3 => count_patterns_len::<3>(guess, solutions, weights, rule),
+        4 => count_patterns_len::<4>(guess, solutions, weights, rule),
+        5 => count_patterns_len::<5>(guess, solutions, weights, rule),
+        6 => count_patterns_len::<6>(guess, solutions, weights, rule),
+        7 => count_patterns_len::<7>(guess, solutions, weights, rule),
+        8 => count_patterns_len::<8>(guess, solutions, weights, rule),
+        _ => unreachable!(),
+    }
+}
+
+fn count_patterns_len<const LEN: usize>(guess: &Word, solutions: &[Word], weights: Option<&[f64]>, rule: DuplicateRule) -> Vec<f64> {
+    let mut pattern_count = vec![0.0; Pattern::count(LEN)];
+    match weights {
+        None => for sol in solutions {
+            let idx = guess.compute_pattern_len(sol, rule, LEN).to_index();
+            pattern_count[idx] += 1.0;
+        },
+        Some(weights) => for (sol, weight) in solutions.iter().zip(weights) {
+            let idx = guess.compute_pattern_len(sol, rule, LEN).to_index();
+            pattern_count[idx] += weight;
+        },
+    }
+
+    pattern_count
+}
@@ -0,0 +1,58 @@
+use std::fs::{create_dir_all, read_to_string, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dataloader::{Date, LoadError};
+use crate::session::state_path;
+
+// A finished interactive game, which is appended to the history as a
+// line of JSON, so that the file never has to be rewritten
+#[derive(Serialize, Deserialize)]
+pub struct GameRecord {
+    pub date: Date,
+    pub language: String,
+    pub guesses: Vec<String>,
+    pub patterns: Vec<String>,
+    // The word that the solver suggested for every guess
+    pub suggestions: Vec<String>,
+    pub solved: bool,
+    // The number of guesses played, including the answer if it was solved
+    pub tries: usize,
+    pub max_guesses: usize,
+    // The suggested words that the game didn't accept
+    pub rejected: Vec<String>,
+}
+
+// Where the history is saved by default
+pub fn default_history_path() -> PathBuf {
+    state_path("history.jsonl")
+}
+
+pub fn append_game(path: &Path, game: &GameRecord) -> io::Result<()> {
+    if let Some(folder) = path.parent().filter(|folder| !folder.as_os_str().is_empty()) {
+        create_dir_all(folder)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(game).unwrap())
+}
+
+// Reads all the games in the history, which is empty if no game was
+// finished yet. Lines that can't be read are reported and skipped, in
+// case the file was cut short while writing it.
+pub fn load_games(path: &Path) -> Result<Vec<GameRecord>, LoadError> {
+    let content = match read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        content => content.map_err(|e| LoadError::UnreadableFile { path: path.display().to_string(), reason: e.to_string() })?,
+    };
+
+    let mut games = vec![];
+    for (i, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(game) => games.push(game),
+            Err(e) => eprintln!("Warning: {}, line {}: {}", path.display(), i + 1, e),
+        }
+    }
+    Ok(games)
+}
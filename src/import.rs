@@ -0,0 +1,18 @@
+use crate::dataloader::ImportReport;
+
+// Prints the word lists that were found in the code of a Wordle, and
+// the ones that were written as the language's lists
+pub fn print_import_report(lang: &str, report: &ImportReport) {
+    println!("Found {} lists of words:", report.lists.len());
+    for list in &report.lists {
+        let file = list.file.map(|file| format!(" -> {}", file)).unwrap_or_default();
+        println!("- {} words of {} letters{}", list.n_words, list.word_length, file);
+    }
+
+    println!();
+    println!("{} of the solutions are also in valid.txt", report.n_shared);
+    if report.wrote_manifest {
+        println!("Wrote a manifest.toml to set the length of the words");
+    }
+    println!("Imported {} into {} with {} guesses and {} solutions", lang, report.folder, report.n_guesses, report.n_solutions);
+}
@@ -1,7 +1,8 @@
-use crate::common::{Pattern, Colors};
+use crate::common::{Pattern, Colors, Word};
+use crate::keys::{EntryMode, KeyBindings};
+use crate::play::{Game, GameUi, Outcome};
 
 use std::io::Write;
-use std::process::exit;
 use crossterm::{cursor, execute, terminal};
 use crossterm::event::{Event, KeyEvent, read, KeyModifiers, KeyCode};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
@@ -9,82 +10,276 @@ use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 // Avoid clashes with termcolor::Color
 type WordleColor = crate::common::Color;
 
+// What the user answered when asked for the pattern of a word
+pub enum PatternInput {
+    Pattern(Pattern),
+    // The game doesn't accept the word
+    Rejected,
+    // The user played another word instead
+    OtherWord,
+    // The user pressed CTRL+C to stop playing
+    Quit,
+}
+
+// The pattern that is being entered for a word. When typing it, the
+// first `pos` tiles already have their colors, and in cycle mode all of
+// them do and `pos` is the tile under the cursor.
+#[derive(Copy, Clone)]
+pub struct PatternEntry {
+    pub pattern: Pattern,
+    pub pos: usize,
+    pub mode: EntryMode,
+}
+
+// Plays the game line by line, asking for the pattern of every word on
+// the line where it's shown
+pub struct LineUi {
+    pub keys: KeyBindings,
+}
+
+impl PatternEntry {
+    pub fn new(len: usize, mode: EntryMode) -> Self {
+        Self { pattern: Pattern::new(len), pos: 0, mode }
+    }
+
+    // The number of tiles from the start that have their colors
+    pub fn colored(&self) -> usize {
+        match self.mode {
+            EntryMode::Type => self.pos,
+            EntryMode::Cycle => self.pattern.len,
+        }
+    }
+
+    // Updates the pattern with a key press, returning what the user
+    // answered once they are done with the word
+    pub fn handle_key(&mut self, ev: &KeyEvent, keys: &KeyBindings) -> Option<PatternInput> {
+        // We're in raw mode so we must take care of processing CTRL+C ourselves
+        if is_ctrl_c(ev) {
+            return Some(PatternInput::Quit);
+        }
+
+        let len = self.pattern.len;
+        match (ev.code, self.mode) {
+            // Process enter if we are done with the pattern, which is
+            // always the case in cycle mode
+            (KeyCode::Enter, _) if self.colored() == len => return Some(PatternInput::Pattern(self.pattern)),
+            // The game doesn't accept the word, so there is no pattern
+            (KeyCode::Char(ch), _) if keys.is_reject(ch) => return Some(PatternInput::Rejected),
+            (KeyCode::Char(ch), _) if keys.is_other_word(ch) => return Some(PatternInput::OtherWord),
+            // Process backspace if the pattern isn't empty
+            (KeyCode::Backspace, _) | (KeyCode::Left, EntryMode::Cycle) if self.pos != 0 => self.pos -= 1,
+            (KeyCode::Right, EntryMode::Cycle) if self.pos + 1 < len => self.pos += 1,
+            (KeyCode::Char(' '), EntryMode::Cycle) => {
+                let color = &mut self.pattern.colors[self.pos];
+                *color = match *color {
+                    Colors::GRAY => Colors::YELLOW,
+                    Colors::YELLOW => Colors::GREEN,
+                    _ => Colors::GRAY,
+                };
+            },
+            // Process the keys of the colors if the pattern isn't full.
+            // In cycle mode the cursor stays on the last tile.
+            (KeyCode::Char(ch), _) if self.pos < len => if let Some(color) = keys.color(ch) {
+                self.pattern.colors[self.pos] = color;
+                if self.mode == EntryMode::Type || self.pos + 1 < len {
+                    self.pos += 1;
+                }
+            },
+            _ => {}
+        }
+        None
+    }
+}
+
+impl GameUi for LineUi {
+    fn start(&mut self, game: &Game, title: &str) {
+        println!("{}", title);
+        if let Some(url) = &game.manifest.url {
+            println!("{}", url);
+        }
+        println!("Use your keyboard to input the pattern that you got for every suggested word.");
+        println!("{}", self.keys.help());
+        println!("-------------------------------------");
+    }
+
+    fn show_turn(&mut self, game: &Game, guess: &Word, pattern: &Pattern) {
+        print_turn(game, guess, pattern);
+    }
+
+    fn thinking(&mut self, _game: &Game) {}
+
+    fn ask_for_pattern(&mut self, game: &Game, guess: &Word, _entropies: &[(Word, f64)]) -> PatternInput {
+        ask_for_pattern(&guess.as_string(&game.translator), &guess.tiles(&game.translator), &self.keys)
+    }
+
+    fn ask_for_word(&mut self, _game: &Game) -> Option<String> {
+        print!("Word played: ");
+        std::io::stdout().flush().unwrap();
+        let mut word = String::new();
+        std::io::stdin().read_line(&mut word).ok()?;
+        Some(word.trim().to_owned()).filter(|word| !word.is_empty())
+    }
+
+    fn ask_yes_no(&mut self, _game: &Game, question: &str) -> bool {
+        ask_yes_no(question)
+    }
+
+    fn warn(&mut self, _game: &Game, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+
+    fn finish(&mut self, game: &Game, outcome: &Outcome) {
+        print_outcome(game, outcome);
+    }
+}
+
+// Prints a word that was already played with its pattern, the same way
+// that it looks after entering the pattern
+pub fn print_turn(game: &Game, guess: &Word, pattern: &Pattern) {
+    print_pattern(&guess.as_string(&game.translator), &guess.tiles(&game.translator), pattern);
+}
+
+// Prints how the game ended, below the turns
+pub fn print_outcome(game: &Game, outcome: &Outcome) {
+    match outcome {
+        // The answer is already on screen
+        Outcome::Solved => {},
+        Outcome::Answer(answer) => print_in_green(&answer.as_string(&game.translator)),
+        Outcome::OutOfGuesses => {
+            let remaining: Vec<String> = game.solutions.iter().map(|w| w.as_string(&game.translator)).collect();
+            println!("Out of guesses! The solution was one of: {}", remaining.join(", "));
+        },
+        Outcome::NoSolutions =>
+            println!("Oops, no solutions found... Check that the color patterns are correct and try again."),
+    }
+}
+
 // Interactively asks the user for the color pattern, updating the
-// current line to reflect the color selections made by the user
-pub fn ask_for_pattern(word: &str) -> Pattern {
+// current line to reflect the color selections made by the user.
+// The word is given as its tiles, which can span several columns. If
+// the word is written differently, like Hangul syllables that are split
+// into jamo, it's shown before the tiles.
+fn ask_for_pattern(word: &str, tiles: &[String], keys: &KeyBindings) -> PatternInput {
     // Enter raw mode to capture the inputs
     terminal::enable_raw_mode()
         .expect("Your console is not compatible with raw mode, which is required to run Eldrow.");
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let mut pos = 0;
-    let mut pattern = Pattern::default();
-    let mut done = false;
-    let mut ctrl_c = false;
-    let chars: Vec<char> = word.chars().collect();
+    let mut entry = PatternEntry::new(tiles.len(), keys.entry);
+    let label = if word == tiles.concat() { String::new() } else { format!("{} ", word) };
 
     // Set the cursor to the beggining of the line
     execute!(stdout, cursor::MoveToColumn(0), cursor::Hide, cursor::DisableBlinking).unwrap();
-    
-    while !done {
-        // Read the next key event
-        match &read_key_blocking() {
-            // We're in raw mode so we must take care of processing CTRL+C ourselves
-            ev if is_ctrl_c(ev) => {
-                ctrl_c = true;
-                break;
-            },
-            // In any other case, process the input:
-            KeyEvent { code, modifiers: _, .. } => match code {
-                // Process enter if we are done with the pattern
-                KeyCode::Enter if pos == 5 => done = true,
-                // Process backspace if the pattern isn't empty
-                KeyCode::Backspace if pos != 0 => {
-                    pos -= 1;
-                    execute!(stdout, cursor::MoveLeft(1)).unwrap();
-                    write!(&mut stdout, "{}", chars[pos]).unwrap();
-                    stdout.flush().unwrap();
-                    execute!(stdout, cursor::MoveLeft(1)).unwrap();
-                }
-                // Process any other keycode if the pattern isn't full
-                KeyCode::Char('x') | KeyCode::Char('y') | KeyCode::Char('g') if pos < 5 => {
-                    // Color the current character
-                    let (color_spec, wordle_color) = get_color_bg(code);
-
-                    // Update the pattern
-                    pattern.colors[pos] = wordle_color;
-
-                    // Print the current character in the correct background color
-                    stdout.set_color(&color_spec).unwrap();
-                    write!(&mut stdout, "{}", chars[pos]).unwrap();
-                    stdout.flush().unwrap();
-                    pos += 1;
-
-                    // Reset the color spec
-                    stdout.reset().unwrap();
-                }
-                
-                _ => {}
-            }
-        };
-    }
+    draw_entry(&mut stdout, &label, tiles, &entry);
 
+    let input = loop {
+        if let Some(input) = entry.handle_key(&read_key_blocking(), keys) {
+            break input;
+        }
+        draw_entry(&mut stdout, &label, tiles, &entry);
+    };
+
+    // Leave the pattern that was entered without the cursor
+    if let PatternInput::Pattern(pattern) = input {
+        draw_word(&mut stdout, &label, tiles, &pattern, pattern.len, None);
+    }
     execute!(stdout, cursor::Show, cursor::EnableBlinking).unwrap(); // Restore to previous state
     terminal::disable_raw_mode().unwrap();  // If we enabled it we should be able to disable it, right...?
 
     // Print a newline in preparation for the next word
+    match input {
+        PatternInput::Rejected => print!(" (rejected)"),
+        PatternInput::OtherWord => print!(" (not played)"),
+        _ => {}
+    }
     println!();
 
-    // If we reached here because of a CTRL+C, end the process
-    if ctrl_c {
-        exit(0);
+    input
+}
+
+// Prints a word that was already played with its pattern, the same way
+// that it looks after entering the pattern
+fn print_pattern(word: &str, tiles: &[String], pattern: &Pattern) {
+    let mut stdout = StandardStream::stdout(ColorChoice::Always);
+    let label = if word == tiles.concat() { String::new() } else { format!("{} ", word) };
+    draw_word(&mut stdout, &label, tiles, pattern, pattern.len, None);
+    println!();
+}
+
+// Asks a yes or no question, where enter or CTRL+C count as a yes
+fn ask_yes_no(question: &str) -> bool {
+    print!("{} [Y/n] ", question);
+    std::io::stdout().flush().unwrap();
+
+    terminal::enable_raw_mode()
+        .expect("Your console is not compatible with raw mode, which is required to run Eldrow.");
+    let answer = loop {
+        if let Some(answer) = yes_no_key(&read_key_blocking()) {
+            break answer;
+        }
+    };
+    terminal::disable_raw_mode().unwrap();
+
+    println!("{}", if answer { "y" } else { "n" });
+    answer
+}
+
+// Updates a word that is being typed with a key press, returning it
+// once enter is pressed, or nothing if the user gives up on it
+pub fn word_key(word: &mut String, ev: &KeyEvent) -> Option<Option<String>> {
+    match ev.code {
+        _ if is_ctrl_c(ev) => Some(None),
+        KeyCode::Esc => Some(None),
+        KeyCode::Enter => Some(Some(word.clone()).filter(|word| !word.is_empty())),
+        KeyCode::Backspace => {
+            word.pop();
+            None
+        },
+        KeyCode::Char(ch) => {
+            word.push(ch);
+            None
+        },
+        _ => None,
     }
+}
+
+// The answer to a yes or no question that a key press stands for, if any
+pub fn yes_no_key(ev: &KeyEvent) -> Option<bool> {
+    match ev.code {
+        _ if is_ctrl_c(ev) => Some(true),
+        KeyCode::Char('n') => Some(false),
+        KeyCode::Char('y') | KeyCode::Enter => Some(true),
+        _ => None,
+    }
+}
 
-    pattern
+// Draws the pattern that is being entered, underlining the tile under
+// the cursor in cycle mode
+fn draw_entry(stdout: &mut StandardStream, label: &str, tiles: &[String], entry: &PatternEntry) {
+    let cursor = (entry.mode == EntryMode::Cycle).then_some(entry.pos);
+    draw_word(stdout, label, tiles, &entry.pattern, entry.colored(), cursor);
+}
+
+// Redraws the whole word, with the first `pos` tiles colored. Tiles
+// can be wider than one column, so the line is printed from the start
+// instead of moving the cursor back and forth.
+fn draw_word(stdout: &mut StandardStream, label: &str, tiles: &[String], pattern: &Pattern, pos: usize, cursor: Option<usize>) {
+    execute!(stdout, cursor::MoveToColumn(0)).unwrap();
+    write!(stdout, "{}", label).unwrap();
+    for (i, tile) in tiles.iter().enumerate() {
+        if i < pos {
+            let mut spec = color_spec(pattern.colors[i]);
+            spec.set_underline(cursor == Some(i));
+            stdout.set_color(&spec).unwrap();
+        }
+        write!(stdout, "{}", tile).unwrap();
+        stdout.reset().unwrap();
+    }
+    stdout.flush().unwrap();
 }
 
 // Prints the final solution with a green background,
 // resetting stdout color afterwards before exiting
-pub fn print_in_green(word: &str) {
+fn print_in_green(word: &str) {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
     stdout.set_color(ColorSpec::new().set_bg(Some(Color::Ansi256(71))).set_fg(Some(Color::Black))).unwrap();
     write!(stdout, "{}", word).unwrap();
@@ -96,26 +291,128 @@ pub fn print_in_green(word: &str) {
 // Pretty self-explanatory
 fn read_key_blocking() -> KeyEvent {
     loop {
-        if let Event::Key(ev) = read().unwrap() {
+        if let Event::Key(ev) = read_event() {
             return ev;
         }
     }
 }
 
-// Gets the correct background color for a keypress
-fn get_color_bg(code: &KeyCode) -> (ColorSpec, WordleColor) {
-    let (spec_color, wordle_color) = match code {
-        KeyCode::Char('x') => (Color::Ansi256(242), Colors::GRAY),
-        KeyCode::Char('y') => (Color::Ansi256(178), Colors::YELLOW),
-        KeyCode::Char('g') => (Color::Ansi256(71), Colors::GREEN),
+pub fn read_event() -> Event {
+    read().unwrap()
+}
+
+// Gets the correct background color to show a tile
+fn color_spec(color: WordleColor) -> ColorSpec {
+    let spec_color = match color {
+        Colors::GRAY => Color::Ansi256(242),
+        Colors::YELLOW => Color::Ansi256(178),
+        Colors::GREEN => Color::Ansi256(71),
         _ => unreachable!(),
     };
 
     let mut spec = ColorSpec::new();
     spec.set_bg(Some(spec_color)).set_fg(Some(Color::Black));
-    (spec, wordle_color)
+    spec
 }
 
 fn is_ctrl_c(ev: &KeyEvent) -> bool {
     matches!(ev, KeyEvent { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL, .. })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(entry: &mut PatternEntry, keys: &KeyBindings, codes: &[KeyCode]) -> Option<PatternInput> {
+        codes.iter().map(|&code| entry.handle_key(&KeyEvent::new(code, KeyModifiers::NONE), keys)).last().flatten()
+    }
+
+    fn chars(s: &str) -> Vec<KeyCode> {
+        s.chars().map(KeyCode::Char).collect()
+    }
+
+    fn colors(entry: &PatternEntry) -> &[WordleColor] {
+        &entry.pattern.colors[..entry.pattern.len]
+    }
+
+    const X: WordleColor = Colors::GRAY;
+    const Y: WordleColor = Colors::YELLOW;
+    const G: WordleColor = Colors::GREEN;
+
+    #[test]
+    fn typing_the_colors() {
+        let keys = KeyBindings::default();
+        let mut entry = PatternEntry::new(5, EntryMode::Type);
+
+        // Enter does nothing until every tile has a color
+        assert!(press(&mut entry, &keys, &chars("xyg")).is_none());
+        assert!(press(&mut entry, &keys, &[KeyCode::Enter]).is_none());
+        assert_eq!(entry.colored(), 3);
+
+        // Backspace takes back the last color, and other keys are ignored
+        press(&mut entry, &keys, &[KeyCode::Backspace, KeyCode::Char('q'), KeyCode::Char(' ')]);
+        assert_eq!(entry.pos, 2);
+        press(&mut entry, &keys, &chars("ggxy"));
+        assert_eq!(colors(&entry), [X, Y, G, G, X]);
+        assert!(matches!(press(&mut entry, &keys, &[KeyCode::Enter]), Some(PatternInput::Pattern(p)) if p.colors[..5] == [X, Y, G, G, X]));
+    }
+
+    #[test]
+    fn cycling_the_colors() {
+        let keys = KeyBindings::default();
+        let mut entry = PatternEntry::new(5, EntryMode::Cycle);
+        assert_eq!(entry.colored(), 5);
+
+        // Space goes from gray to yellow to green and back to gray
+        press(&mut entry, &keys, &chars(" "));
+        assert_eq!(colors(&entry), [Y, X, X, X, X]);
+        press(&mut entry, &keys, &chars("  "));
+        assert_eq!(colors(&entry), [X, X, X, X, X]);
+
+        // The arrows move the cursor, which stops at both ends
+        press(&mut entry, &keys, &[KeyCode::Left, KeyCode::Right, KeyCode::Right, KeyCode::Char(' '), KeyCode::Char(' ')]);
+        assert_eq!(colors(&entry), [X, X, G, X, X]);
+        press(&mut entry, &keys, &[KeyCode::Right; 10]);
+        assert_eq!(entry.pos, 4);
+        press(&mut entry, &keys, &[KeyCode::Left; 10]);
+        assert_eq!(entry.pos, 0);
+
+        // The keys of the colors also work, and the cursor stays on the
+        // last tile
+        press(&mut entry, &keys, &chars("yyxxgg"));
+        assert_eq!(colors(&entry), [Y, Y, X, X, G]);
+        assert_eq!(entry.pos, 4);
+
+        // Every tile has a color, so the pattern can be submitted anytime
+        assert!(matches!(press(&mut entry, &keys, &[KeyCode::Enter]), Some(PatternInput::Pattern(_))));
+    }
+
+    #[test]
+    fn typing_another_word() {
+        let keys = KeyBindings::default();
+        let mut entry = PatternEntry::new(5, EntryMode::Type);
+        assert!(matches!(press(&mut entry, &keys, &chars("xyw")), Some(PatternInput::OtherWord)));
+
+        let mut word = String::new();
+        let mut type_key = |code| word_key(&mut word, &KeyEvent::new(code, KeyModifiers::NONE));
+        assert!(chars("crx").into_iter().all(|code| type_key(code).is_none()));
+        assert!(type_key(KeyCode::Backspace).is_none());
+        assert!(chars("ane").into_iter().all(|code| type_key(code).is_none()));
+        assert_eq!(type_key(KeyCode::Enter), Some(Some("crane".to_owned())));
+
+        // The word can be left without playing it
+        assert_eq!(word_key(&mut "cr".to_owned(), &KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), Some(None));
+        assert_eq!(word_key(&mut String::new(), &KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), Some(None));
+    }
+
+    #[test]
+    fn rejecting_and_quitting() {
+        let keys = KeyBindings::default();
+        for mode in [EntryMode::Type, EntryMode::Cycle] {
+            let mut entry = PatternEntry::new(5, mode);
+            assert!(matches!(press(&mut entry, &keys, &chars("xr")), Some(PatternInput::Rejected)));
+            let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+            assert!(matches!(entry.handle_key(&ctrl_c, &keys), Some(PatternInput::Quit)));
+        }
+    }
+}
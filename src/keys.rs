@@ -0,0 +1,258 @@
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::common::{Color, Colors};
+use crate::dataloader::LoadError;
+use crate::dirs::xdg_file;
+
+// The sets of keys that the colors can be entered with, where the
+// default one uses the first letter of every color
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyScheme {
+    Xyg,
+    // 0 for gray, 1 for yellow and 2 for green
+    Digits,
+    // b for black instead of x
+    Byg,
+    // . for gray, ? for yellow and ! for green
+    Symbols,
+}
+
+// How the pattern of a word is entered
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryMode {
+    // The colors are typed from the first tile to the last one
+    Type,
+    // Every tile starts gray, and space changes the color of the tile
+    // under the cursor, which is moved with the arrow keys
+    Cycle,
+}
+
+// The keys.toml file in the config folder. Every field is optional, and
+// the keys that are given replace the ones of the scheme.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeysFile {
+    scheme: KeyScheme,
+    entry: EntryMode,
+    gray: Option<String>,
+    yellow: Option<String>,
+    green: Option<String>,
+    reject: Option<String>,
+    other_word: Option<String>,
+}
+
+impl Default for KeysFile {
+    fn default() -> Self {
+        Self { scheme: KeyScheme::Xyg, entry: EntryMode::Type, gray: None, yellow: None, green: None, reject: None, other_word: None }
+    }
+}
+
+// The keys that are used to enter the patterns, where every action can
+// have several keys
+#[derive(Clone)]
+pub struct KeyBindings {
+    pub entry: EntryMode,
+    gray: Vec<char>,
+    yellow: Vec<char>,
+    green: Vec<char>,
+    reject: Vec<char>,
+    other_word: Vec<char>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::from_file(KeysFile::default()).unwrap()
+    }
+}
+
+impl KeyBindings {
+    // Reads the key bindings from a file, which are the default ones if
+    // the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        let path_name = path.display().to_string();
+        let content = match read_to_string(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            content => content.map_err(|e| LoadError::UnreadableFile { path: path_name.clone(), reason: e.to_string() })?,
+        };
+        toml::from_str(&content).map_err(|e| e.to_string())
+            .and_then(Self::from_file)
+            .map_err(|reason| LoadError::BadKeyBindings { path: path_name, reason })
+    }
+
+    fn from_file(file: KeysFile) -> Result<Self, String> {
+        let (gray, yellow, green) = match file.scheme {
+            KeyScheme::Xyg => ("x", "y", "g"),
+            KeyScheme::Digits => ("0", "1", "2"),
+            KeyScheme::Byg => ("b", "y", "g"),
+            KeyScheme::Symbols => (".", "?", "!"),
+        };
+        let keys = |given: Option<String>, default: &str| given.unwrap_or_else(|| default.to_owned()).chars().collect();
+        let bindings = Self {
+            entry: file.entry,
+            gray: keys(file.gray, gray),
+            yellow: keys(file.yellow, yellow),
+            green: keys(file.green, green),
+            reject: keys(file.reject, "r"),
+            other_word: keys(file.other_word, "w"),
+        };
+
+        // Every key can only do one thing, and space is taken in cycle mode
+        let mut all: Vec<char> = bindings.actions().iter().flat_map(|(keys, _)| keys.iter().copied()).collect();
+        if bindings.entry == EntryMode::Cycle {
+            all.push(' ');
+        }
+        all.sort_unstable();
+        if let Some(pair) = all.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(format!("{} is bound to more than one action", key_name(pair[0])));
+        }
+        if let Some((_, name)) = bindings.actions().iter().find(|(keys, _)| keys.is_empty()) {
+            return Err(format!("there are no keys for {}", name));
+        }
+        Ok(bindings)
+    }
+
+    fn actions(&self) -> [(&[char], &str); 5] {
+        [
+            (&self.green, "Green"),
+            (&self.yellow, "Yellow"),
+            (&self.gray, "Gray"),
+            (&self.reject, "rejected words"),
+            (&self.other_word, "playing another word"),
+        ]
+    }
+
+    // The color that a key enters, if any
+    pub fn color(&self, key: char) -> Option<Color> {
+        if self.gray.contains(&key) {
+            Some(Colors::GRAY)
+        } else if self.yellow.contains(&key) {
+            Some(Colors::YELLOW)
+        } else if self.green.contains(&key) {
+            Some(Colors::GREEN)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_reject(&self, key: char) -> bool {
+        self.reject.contains(&key)
+    }
+
+    pub fn is_other_word(&self, key: char) -> bool {
+        self.other_word.contains(&key)
+    }
+
+    // A line that explains every key, for the current bindings
+    pub fn help(&self) -> String {
+        let mut parts: Vec<String> = vec![];
+        if self.entry == EntryMode::Cycle {
+            parts.push("space: change color".to_owned());
+            parts.push("left/right: move".to_owned());
+        }
+        for (keys, name) in &self.actions()[..3] {
+            parts.push(format!("{}: {}", keys_name(keys), name));
+        }
+        parts.push("enter: submit".to_owned());
+        if self.entry == EntryMode::Type {
+            parts.push("backspace: go back".to_owned());
+        }
+        parts.push(format!("{}: not accepted", keys_name(&self.reject)));
+        parts.push(format!("{}: played another word", keys_name(&self.other_word)));
+        parts.push("Ctrl+C: quit".to_owned());
+        parts.join(", ")
+    }
+}
+
+fn keys_name(keys: &[char]) -> String {
+    keys.iter().map(|&key| key_name(key)).collect::<Vec<_>>().join("/")
+}
+
+fn key_name(key: char) -> String {
+    match key {
+        ' ' => "space".to_owned(),
+        key => key.to_string(),
+    }
+}
+
+// Where the key bindings are read from by default, which is the eldrow
+// folder inside the XDG config home (~/.config)
+pub fn default_keys_path() -> PathBuf {
+    xdg_file("XDG_CONFIG_HOME", ".config", "keys.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(content: &str) -> Result<KeyBindings, String> {
+        toml::from_str(content).map_err(|e| e.to_string()).and_then(KeyBindings::from_file)
+    }
+
+    #[test]
+    fn default_keys() {
+        let keys = bindings("").unwrap();
+        assert!(keys.entry == EntryMode::Type);
+        assert_eq!(keys.color('x'), Some(Colors::GRAY));
+        assert_eq!(keys.color('y'), Some(Colors::YELLOW));
+        assert_eq!(keys.color('g'), Some(Colors::GREEN));
+        assert_eq!(keys.color('b'), None);
+        assert!(keys.is_reject('r'));
+        assert!(keys.is_other_word('w'));
+    }
+
+    #[test]
+    fn schemes() {
+        for (scheme, gray, yellow, green) in [("digits", '0', '1', '2'), ("byg", 'b', 'y', 'g'), ("symbols", '.', '?', '!')] {
+            let keys = bindings(&format!("scheme = \"{}\"", scheme)).unwrap();
+            assert_eq!(keys.color(gray), Some(Colors::GRAY), "{}", scheme);
+            assert_eq!(keys.color(yellow), Some(Colors::YELLOW), "{}", scheme);
+            assert_eq!(keys.color(green), Some(Colors::GREEN), "{}", scheme);
+            assert_eq!(keys.color('x'), None, "{}", scheme);
+        }
+        assert!(bindings("scheme = \"qwerty\"").is_err());
+    }
+
+    // The keys that are given replace the ones of the scheme, and an
+    // action can have several of them
+    #[test]
+    fn overrides() {
+        let keys = bindings("scheme = \"digits\"\ngray = \"x0\"\nreject = \"-\"").unwrap();
+        assert_eq!(keys.color('x'), Some(Colors::GRAY));
+        assert_eq!(keys.color('0'), Some(Colors::GRAY));
+        assert_eq!(keys.color('1'), Some(Colors::YELLOW));
+        assert!(keys.is_reject('-'));
+        assert!(!keys.is_reject('r'));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        assert_eq!(bindings("scheme = \"byg\"\nreject = \"b\"").err().unwrap(), "b is bound to more than one action");
+        assert!(bindings("yellow = \"yg\"").is_err());
+        assert!(bindings("green = \"\"").is_err());
+        assert_eq!(bindings("other_word = \"x\"").err().unwrap(), "x is bound to more than one action");
+        assert!(bindings("color = \"x\"").is_err());
+    }
+
+    // Space changes the colors in cycle mode, so it can't be bound to
+    // anything else, but it can be used when typing the colors
+    #[test]
+    fn space_in_cycle_mode() {
+        assert_eq!(bindings("entry = \"cycle\"\ngray = \" \"").err().unwrap(), "space is bound to more than one action");
+        assert!(bindings("entry = \"type\"\ngray = \" \"").unwrap().color(' ') == Some(Colors::GRAY));
+        assert!(bindings("entry = \"cycle\"").unwrap().entry == EntryMode::Cycle);
+    }
+
+    #[test]
+    fn help_lists_the_current_keys() {
+        let help = bindings("scheme = \"symbols\"\nentry = \"cycle\"").unwrap().help();
+        assert!(help.starts_with("space: change color, left/right: move, !: Green, ?: Yellow, .: Gray, enter: submit, r: not accepted"));
+        assert!(!help.contains("backspace"));
+        assert!(bindings("gray = \"x0\"").unwrap().help().contains("x/0: Gray, enter: submit, backspace: go back"));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::dataloader::LintReport;
+
+// Prints the issues found in a language's word lists, followed by a
+// summary of the language's size
+pub fn print_lint_report(lang: &str, report: &LintReport) {
+    for issue in &report.issues {
+        match issue.line {
+            Some(line) => println!("{}, line {}: {}", issue.path, line, issue.message),
+            None => println!("{}: {}", issue.path, issue.message),
+        }
+    }
+
+    if !report.issues.is_empty() {
+        println!();
+    }
+
+    println!("Summary for {}:", lang);
+    println!("- Alphabet: {} letters", report.alphabet_size);
+    println!("- valid.txt: {} words", report.n_valid);
+    println!("- solutions.txt: {} words", report.n_solutions);
+    println!("- Guesses: {} words", report.n_guesses);
+
+    if let Some(fixed) = report.fixed {
+        println!("Fixed {} issues", fixed);
+    }
+
+    match report.issues.len() {
+        0 => println!("No issues found"),
+        n => println!("{} issues found", n),
+    }
+}
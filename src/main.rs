@@ -1,34 +1,353 @@
 mod benchmark;
+mod board;
+mod cheatsheet;
 mod common;
+mod corpus;
 mod dataloader;
+mod dirs;
 mod entropy;
+mod history;
+mod import;
 mod input;
+mod keys;
+mod lint;
+mod model;
+mod openers;
 mod play;
+mod report;
+mod session;
+mod stats;
 
-use clap::Parser;
+use std::path::PathBuf;
+use std::process::exit;
+use clap::{Parser, Subcommand};
 
 use benchmark::Benchmark;
+use cheatsheet::print_cheat_sheet;
+use corpus::print_corpus_report;
+use dataloader::{CorpusOptions, DataLoader, Date, ListOverrides, LoadError, OverlayEdit};
+use entropy::{Metric, Scoring, Strategy};
+use history::{default_history_path, load_games};
+use import::print_import_report;
+use keys::{default_keys_path, KeyBindings};
+use lint::print_lint_report;
+use model::GuessModel;
+use openers::print_openers;
 use play::interactive_play;
+use report::BenchmarkReport;
+use session::{default_session_path, Session};
+use stats::print_stats;
 
 #[derive(Parser)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     #[clap(short, long, action)]
     benchmark: bool,
 
-    #[clap(short, long, default_value = "en")]
+    /// Play line by line instead of on a full-screen board
+    #[clap(long, action)]
+    plain: bool,
+
+    /// File with the keys used to enter the patterns, which is inside
+    /// the XDG config home (~/.config/eldrow/keys.toml) by default
+    #[clap(long)]
+    keys: Option<PathBuf>,
+
+    /// Continue the last game that was interrupted, with the language
+    /// and options that it was started with
+    #[clap(long, action)]
+    resume: bool,
+
+    /// File where the game is saved after every turn, which is inside
+    /// the XDG state home (~/.local/state/eldrow) by default
+    #[clap(long)]
+    session: Option<PathBuf>,
+
+    /// File where the finished games are logged, which is next to the
+    /// saved game by default
+    #[clap(long, global = true)]
+    history: Option<PathBuf>,
+
+    #[clap(short, long, default_value = "en", global = true)]
     language: String,
+
+    /// Folder to look up the languages in before the default ones
+    #[clap(long, global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// Locale whose casing rules are used instead of the language's one, e.g. tr
+    #[clap(long, global = true)]
+    locale: Option<String>,
+
+    /// Day being played, as YYYY-MM-DD, which is today in UTC by default
+    #[clap(long, value_parser, global = true)]
+    date: Option<Date>,
+
+    /// File with the valid guesses to use instead of the language's ones
+    #[clap(long, global = true)]
+    guesses: Option<PathBuf>,
+
+    /// File with the answers to use instead of the language's ones
+    #[clap(long, global = true)]
+    solutions: Option<PathBuf>,
+
+    /// Add the words in --guesses and --solutions to the language's
+    /// lists instead of replacing them
+    #[clap(long, action, global = true)]
+    extend_lists: bool,
+
+    /// Version of the language's answers to use, read from
+    /// solutions.VARIANT.txt instead of solutions.txt
+    #[clap(long, global = true)]
+    variant: Option<String>,
+
+    /// Keep the answers that were already used as possible solutions
+    #[clap(long, action, global = true)]
+    keep_used: bool,
+
+    /// Only allow guesses that reuse all the revealed hints
+    #[clap(long, action, global = true)]
+    hard: bool,
+
+    /// How to score the guesses when choosing the next one
+    #[clap(long, value_enum, default_value = "entropy", global = true)]
+    scoring: Scoring,
+
+    /// Benchmark results file with the guess model to use when scoring
+    /// by expected guesses, instead of the default one
+    #[clap(long, global = true)]
+    model: Option<String>,
+
+    /// Number of slowest solves to list after the benchmark
+    #[clap(long, default_value_t = 10)]
+    outliers: usize,
+
+    /// File to save the benchmark results to
+    #[clap(long)]
+    save: Option<String>,
+
+    /// Benchmark results file to compare the current run against
+    #[clap(long)]
+    compare: Option<String>,
+
+    /// Maximum increase in the average number of guesses with respect
+    /// to the compared results before the run is considered a regression
+    #[clap(long, default_value_t = 0.0)]
+    max_regression: f64,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the best opening words for the language
+    Openers {
+        /// Metric used to rank the opening words
+        #[clap(short, long, value_enum, default_value = "entropy")]
+        metric: Metric,
+
+        /// Number of opening words to list
+        #[clap(short = 'n', long, default_value_t = 10)]
+        top: usize,
+
+        /// Run the full benchmark for this many of the top opening words
+        #[clap(short, long, default_value_t = 0)]
+        sweep: usize,
+    },
+
+    /// Print the best second guess for every pattern of the opening word
+    CheatSheet {
+        /// Opening word to use instead of the best one for the language
+        #[clap(short, long)]
+        opener: Option<String>,
+
+        /// Print the cheat sheet as JSON instead of a table
+        #[clap(long, action)]
+        json: bool,
+    },
+
+    /// Check the language's word lists for problems
+    Lint {
+        /// Rewrite the word lists without the problems that can be fixed
+        #[clap(long, action)]
+        fix: bool,
+    },
+
+    /// Extract the word lists from the saved JS code of a Wordle into
+    /// the language's folder in the data folder
+    Import {
+        /// JS file that contains the word lists
+        bundle: PathBuf,
+
+        /// Replace the word lists if the language already has them
+        #[clap(long, action)]
+        force: bool,
+    },
+
+    /// Build the language's word lists from the words of the right length
+    /// in a plain text corpus, using the most frequent ones as solutions
+    Corpus {
+        /// Text file to count the words in
+        corpus: PathBuf,
+
+        /// Minimum number of times that a word must appear to be valid
+        #[clap(long, default_value_t = 2)]
+        min_count: usize,
+
+        /// Number of the most frequent words to use as solutions
+        #[clap(long, default_value_t = 2000)]
+        answers: usize,
+
+        /// Replace the word lists if the language already has them
+        #[clap(long, action)]
+        force: bool,
+    },
+
+    /// Record the day's answer as used, so that it's left out of the
+    /// solutions from the next day on
+    Used {
+        /// Answer to record, which is taken from the language's schedule if missing
+        word: Option<String>,
+    },
+
+    /// Show the statistics of the finished games for every language
+    Stats,
+
+    /// Add a change to the language's word lists, which is saved apart
+    /// from them, in the overlay.txt file of the language
+    Overlay {
+        /// Change to make to the word lists
+        #[clap(value_enum)]
+        edit: OverlayEdit,
+
+        /// Word that the change applies to
+        word: String,
+    },
 }
 
 fn main() {
-    // Parse the command-line arguments
+    // Parse the command-line arguments and run the requested mode,
+    // reporting any problem with the language's data before exiting
     let args = Args::parse();
+    if let Err(err) = run(args) {
+        eprintln!("Error: {}", err);
+        exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), LoadError> {
     let lang = args.language;
+    let date = args.date.unwrap_or_else(Date::today);
+    let lists = ListOverrides {
+        guesses: args.guesses,
+        solutions: args.solutions,
+        extend: args.extend_lists,
+        variant: args.variant,
+    };
+    let loader = DataLoader::new(args.data_dir.as_deref(), args.locale.clone(), (!args.keep_used).then_some(date), lists.clone());
+    let strategy = match args.scoring {
+        Scoring::Entropy => Strategy::Entropy,
+        Scoring::ExpectedGuesses => Strategy::ExpectedGuesses(load_model(args.model.as_deref())?),
+    };
+
+    match args.command {
+        Some(Command::Openers { metric, top, sweep }) => {
+            let mut bench = Benchmark::init(&loader, &lang, args.hard, strategy)?;
+            print_openers(&mut bench, metric, top, sweep);
+        },
+        Some(Command::CheatSheet { opener, json }) => {
+            let mut bench = Benchmark::init(&loader, &lang, args.hard, strategy)?;
+            if let Some(opener) = opener {
+                match bench.parse_guess(&opener) {
+                    Some(word) => bench.set_opener(word),
+                    None => {
+                        eprintln!("{} is not a valid guess for this language", opener);
+                        exit(1);
+                    }
+                }
+            }
+            print_cheat_sheet(&bench, json);
+        },
+        Some(Command::Lint { fix }) => {
+            let report = loader.lint_language(&lang, fix)?;
+            print_lint_report(&lang, &report);
+            if !report.issues.is_empty() {
+                exit(1);
+            }
+        },
+        Some(Command::Import { bundle, force }) => {
+            let data_dir = args.data_dir.unwrap_or_else(|| PathBuf::from("data"));
+            let report = loader.import_language(&bundle, &data_dir, &lang, force)?;
+            print_import_report(&lang, &report);
+        },
+        Some(Command::Corpus { corpus, min_count, answers, force }) => {
+            let data_dir = args.data_dir.unwrap_or_else(|| PathBuf::from("data"));
+            let options = CorpusOptions { min_count, n_answers: answers, force };
+            let report = loader.build_language(&corpus, &data_dir, &lang, &options)?;
+            print_corpus_report(&lang, &report);
+        },
+        Some(Command::Used { word }) => {
+            let (word, added) = loader.record_answer(&lang, word.as_deref(), date)?;
+            if added {
+                println!("Recorded {} as the answer for {}", word, date);
+            } else {
+                println!("{} was already recorded as used", word);
+            }
+        },
+        Some(Command::Stats) => {
+            let history_path = args.history.unwrap_or_else(default_history_path);
+            let games = load_games(&history_path)?;
+            print_stats(&games);
+        },
+        Some(Command::Overlay { edit, word }) => {
+            loader.edit_overlay(&lang, edit, &word)?;
+            println!("Saved the change to the overlay of {}", lang);
+        },
+        // If the benchmark flag is active, run it, otherwise play the game
+        None if args.benchmark => {
+            // The baseline is read first, so that a bad file doesn't
+            // waste the whole run
+            let baseline = args.compare.as_deref().map(BenchmarkReport::load).transpose()?;
+            let bench = Benchmark::init(&loader, &lang, args.hard, strategy)?;
+            let report = bench.run(args.outliers);
 
-    // If the benchmark flag is active, run it, otherwise play the game
-    if args.benchmark {
-        let bench = Benchmark::init(&lang);
-        bench.run();
-    } else {
-        interactive_play(&lang);
+            if let Some(path) = &args.save {
+                report.save(path)?;
+            }
+
+            // Compare with the baseline if there is one, signaling
+            // any regression through the exit code
+            if let Some(baseline) = &baseline {
+                if report.compare(baseline, args.max_regression) {
+                    exit(1);
+                }
+            }
+        },
+        None => {
+            let session_path = args.session.unwrap_or_else(default_session_path);
+            let session = if args.resume {
+                Session::load(&session_path)?
+            } else {
+                Session::new(&lang, date, args.hard, strategy).with_lists(args.keep_used, args.locale, lists)
+            };
+            // A resumed game uses the lists that it was started with,
+            // instead of the ones for the current options
+            let loader = session.loader(args.data_dir.as_deref());
+            let history_path = args.history.unwrap_or_else(default_history_path);
+            let keys = KeyBindings::load(&args.keys.unwrap_or_else(default_keys_path))?;
+            interactive_play(&loader, session, &session_path, &history_path, args.plain, keys)?
+        },
     }
+
+    Ok(())
+}
+
+// Reads the guess model fitted by a previous benchmark, if any
+fn load_model(path: Option<&str>) -> Result<GuessModel, LoadError> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(GuessModel::default()),
+    };
+
+    BenchmarkReport::load(path)?.model.ok_or_else(|| LoadError::NoGuessModel { path: path.to_owned() })
 }
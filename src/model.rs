@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+// Estimates how many guesses it takes to find the answer when there is
+// some amount of uncertainty left, measured in bits. It's fitted from
+// benchmark runs, where every turn tells how much uncertainty there was
+// before it and how many guesses were still needed from then on, as
+// f(H) = 1 + a * H + b * log2(1 + H), which is 1 when the answer is known.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct GuessModel {
+    pub a: f64,
+    pub b: f64,
+}
+
+// Fitted from the English benchmark, which works well enough for other
+// languages until they are benchmarked themselves
+impl Default for GuessModel {
+    fn default() -> Self {
+        Self { a: 0.094, b: 0.388 }
+    }
+}
+
+impl GuessModel {
+    // Fits the model to pairs of (bits of uncertainty, guesses needed)
+    // by least squares
+    pub fn fit(points: &[(f64, f64)]) -> Self {
+        // Solve the normal equations for the two coefficients, with
+        // x1 = H, x2 = log2(1 + H) and y = guesses - 1
+        let (mut s11, mut s12, mut s22, mut s1y, mut s2y) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for &(bits, guesses) in points {
+            let (x1, x2, y) = (bits, (1.0 + bits).log2(), guesses - 1.0);
+            s11 += x1 * x1;
+            s12 += x1 * x2;
+            s22 += x2 * x2;
+            s1y += x1 * y;
+            s2y += x2 * y;
+        }
+
+        let det = s11 * s22 - s12 * s12;
+        if det.abs() < f64::EPSILON {
+            return Self::default();
+        }
+
+        Self {
+            a: (s22 * s1y - s12 * s2y) / det,
+            b: (s11 * s2y - s12 * s1y) / det,
+        }
+    }
+
+    // The expected number of guesses needed with this many bits left
+    pub fn guesses(&self, bits: f64) -> f64 {
+        let bits = bits.max(0.0);
+        1.0 + self.a * bits + self.b * (1.0 + bits).log2()
+    }
+
+    pub fn describe(&self) -> String {
+        format!("1 + {:.4} * H + {:.4} * log2(1 + H)", self.a, self.b)
+    }
+}
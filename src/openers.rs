@@ -0,0 +1,36 @@
+use crate::benchmark::{Benchmark, average};
+use crate::entropy::Metric;
+
+// Lists the best opening words for a language according to a metric.
+// Optionally, the full benchmark is run for the first `sweep` of them
+// to find out the actual average number of guesses for each opener.
+pub fn print_openers(bench: &mut Benchmark, metric: Metric, n: usize, sweep: usize) {
+    let ranking = bench.rank_openers(metric, n.max(sweep));
+
+    println!("Top {} opening words by {}:", n, metric.name());
+    for (i, (word, score)) in ranking.iter().take(n).enumerate() {
+        println!("{:>4}. {} {:.4}", i + 1, word.as_string(bench.translator()), score);
+    }
+
+    if sweep == 0 {
+        return;
+    }
+
+    let mut results = Vec::with_capacity(sweep);
+    for (i, &(word, score)) in ranking.iter().take(sweep).enumerate() {
+        println!("Benchmarking opener {}/{}: {}", i + 1, sweep, word.as_string(bench.translator()));
+        bench.set_opener(word);
+        let rounds = bench.play_all();
+        let failures = rounds.iter().filter(|r| !r.is_solved()).count();
+        results.push((word, score, average(&rounds), failures));
+    }
+
+    // Show the benchmarked openers from best to worst average
+    results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+
+    println!();
+    println!("{:>4}  {:<10} {:>10} {:>8} {:>6}", "#", "Opener", metric.name(), "Average", "X");
+    for (i, (word, score, avg, failures)) in results.iter().enumerate() {
+        println!("{:>4}. {:<10} {:>10.4} {:>8.4} {:>6}", i + 1, word.as_string(bench.translator()), score, avg, failures);
+    }
+}
@@ -1,53 +1,244 @@
-use crate::common::MatchInfo;
-use crate::dataloader::DataLoader;
-use crate::entropy::find_best_splitter;
-use crate::input::{ask_for_pattern, print_in_green};
+use crate::board::BoardUi;
+use crate::common::{Colors, MatchInfo, Pattern, Word, MAX_WORD_LEN};
+use crate::dataloader::{CharTranslator, DataLoader, Language, LoadError, Manifest, OverlayEdit, Priors};
+use crate::entropy::{choose_from_entropies, guess_entropies, uncertainty};
+use crate::history::{append_game, GameRecord};
+use crate::input::{LineUi, PatternInput};
+use crate::keys::KeyBindings;
+use crate::session::{Session, Turn};
+
+use std::mem::take;
+use std::path::Path;
 
 use rayon::prelude::*;
 
-// Play an interactive guessing game with the user
-pub fn interactive_play(lang: &str) {
-    let (guesses, mut solutions, translator) = DataLoader::load_language(lang);
-    let n_chars = translator.count();
-
-    println!("Use your keyboard to input the pattern that you got for every suggested word.");
-    println!("g: Green, y: Yellow, x: Gray");
-    println!("Enter to submit, backspace to go back.");
-    println!("-------------------------------------");
-
-    // A flag to remember if we guessed the solution by chance before
-    // we were done pruning the solutions space, to avoid printing
-    // it twice at the end of the game
-    let mut guessed_midway = false;
-
-    // Keep guessing until we only have one possible solution or we guess the word
-    while solutions.len() > 1 {
-        let guess = if solutions.len() == 2 {
-            // If there is only two possible solutions left, we use
-            // one of them, since we'll be right 50% of the time and
-            // we aren't worsening the worse case if we miss.
-            solutions[0]
-        } else {
-            find_best_splitter(&guesses, &solutions)
-        };
+// The state of an interactive game, which is what the user interface
+// shows along with the word that is being asked for
+pub struct Game {
+    pub translator: CharTranslator,
+    pub manifest: Manifest,
+    pub priors: Option<Priors>,
+    pub hard_mode: bool,
+    pub guesses: Vec<Word>,
+    pub solutions: Vec<Word>,
+    // The turns that were played, along with the information that each
+    // of them gained, in bits
+    pub turns: Vec<(Word, Pattern)>,
+    pub bits: Vec<f64>,
+}
+
+// How a game ended
+pub enum Outcome {
+    // The last guess was the answer
+    Solved,
+    // Only the answer is left, which is the next word to play, unless
+    // there are no guesses left
+    Answer(Word),
+    // There were still several solutions when the guesses ran out
+    OutOfGuesses,
+    // Some pattern was wrong, since no solution matches all of them
+    NoSolutions,
+}
 
-        let guess_string = guess.as_string(&translator);
-        print!("{}", guess_string);
-        let pattern = ask_for_pattern(&guess_string);
+// The ways of showing the game and asking the user for the patterns
+pub trait GameUi {
+    fn start(&mut self, game: &Game, title: &str);
+    // Shows a turn that was played before the game was resumed
+    fn show_turn(&mut self, game: &Game, guess: &Word, pattern: &Pattern);
+    // Shows that the next guess is being chosen, which can take a while
+    fn thinking(&mut self, game: &Game);
+    // Asks for the pattern of the guess, given the expected entropy of
+    // every word that could be guessed instead
+    fn ask_for_pattern(&mut self, game: &Game, guess: &Word, entropies: &[(Word, f64)]) -> PatternInput;
+    // Asks for the word that the user played instead of the suggested
+    // one, if they didn't change their mind
+    fn ask_for_word(&mut self, game: &Game) -> Option<String>;
+    fn ask_yes_no(&mut self, game: &Game, question: &str) -> bool;
+    // Reports a problem that isn't worth stopping the game for
+    fn warn(&mut self, game: &Game, message: &str);
+    // Shows how the game ended, leaving the terminal as it was before
+    fn finish(&mut self, game: &Game, outcome: &Outcome);
+}
 
-        // If we randomly guessed it, remember it and finish playing
+impl Game {
+    // Keeps the solutions that match the pattern of a guess. In hard mode,
+    // the next guesses must also reuse the hints.
+    fn play(&mut self, guess: Word, pattern: Pattern) {
+        let before = uncertainty(&self.solutions, self.priors.as_ref());
         if pattern.is_solved() {
-            guessed_midway = true;
+            self.solutions = vec![guess];
+        } else {
+            let match_data = MatchInfo::from_word_match(&guess, &pattern, self.translator.count(), self.manifest.duplicates);
+            self.solutions = take(&mut self.solutions).into_par_iter().filter(|w| match_data.matches(w)).collect();
+            if self.hard_mode {
+                self.guesses = take(&mut self.guesses).into_par_iter().filter(|w| match_data.allows_in_hard_mode(w)).collect();
+            }
+        }
+
+        let after = if self.solutions.is_empty() { 0.0 } else { uncertainty(&self.solutions, self.priors.as_ref()) };
+        self.bits.push(before - after);
+        self.turns.push((guess, pattern));
+    }
+
+    // Whether the last guess was the answer
+    pub fn is_solved(&self) -> bool {
+        self.turns.last().is_some_and(|(_, pattern)| pattern.is_solved())
+    }
+
+    // Parses a word that the user played, as long as it can be guessed
+    fn parse_guess(&self, word: &str) -> Option<Word> {
+        self.translator.parse(word).filter(|word| self.guesses.contains(word))
+    }
+}
+
+// Play an interactive guessing game with the user, continuing from the
+// turns in the session, which is saved to the given path after every
+// turn. Once the game is over, it's added to the history. The game is
+// shown on a full-screen board, or line by line in plain mode, and the
+// patterns are entered with the given keys.
+pub fn interactive_play(loader: &DataLoader, mut session: Session, session_path: &Path, history_path: &Path, plain: bool, keys: KeyBindings) -> Result<(), LoadError> {
+    let lang = session.language.clone();
+    let Language { guesses, solutions, translator, manifest, priors, .. } = loader.load_language(&lang)?;
+
+    // Read the turns of the session before taking over the terminal, so
+    // that any problem with them can be reported
+    let turns: Vec<(Word, Pattern)> = session.turns.iter().map(|turn| {
+        match (translator.parse(&turn.guess), Pattern::parse(&turn.pattern)) {
+            (Some(guess), Some(pattern)) if pattern.len == guess.len => Ok((guess, pattern)),
+            _ => Err(LoadError::BadSession {
+                path: session_path.display().to_string(),
+                reason: format!("invalid turn: {} {}", turn.guess, turn.pattern),
+            }),
+        }
+    }).collect::<Result<_, _>>()?;
+
+    let mut game = Game { translator, manifest, priors, hard_mode: session.hard_mode, guesses, solutions, turns: vec![], bits: vec![] };
+    let title = game.manifest.display_name(&lang);
+    let mut ui: Box<dyn GameUi> = if plain { Box::new(LineUi { keys }) } else { Box::new(BoardUi::new(keys)) };
+    ui.start(&game, &title);
+
+    // The words that were rejected before are left out again, in case
+    // they couldn't be added to the overlay
+    let rejected: Vec<Word> = session.rejected.iter().filter_map(|word| game.translator.parse(word)).collect();
+    game.guesses.retain(|w| !rejected.contains(w));
+    game.solutions.retain(|w| !rejected.contains(w));
+
+    // Replay the turns of the session, which is the same as playing them
+    // again without asking for the patterns
+    for (guess, pattern) in turns {
+        game.play(guess, pattern);
+        ui.show_turn(&game, &guess, &pattern);
+        if game.is_solved() {
             break;
         }
+    }
 
-        let match_data = MatchInfo::from_word_match(&guess, &pattern, n_chars);
-        solutions = solutions.into_par_iter().filter(|w| match_data.matches(w)).collect();
+    // Keep guessing until we only have one possible solution, we guess
+    // the word, or we run out of guesses
+    let max_guesses = game.manifest.max_guesses;
+    while !game.is_solved() && game.solutions.len() > 1 && game.turns.len() < max_guesses {
+        // The entropies are computed once, both to choose the guess and
+        // to show how it compares to the other words
+        ui.thinking(&game);
+        let entropies = guess_entropies(&game.guesses, &game.solutions, game.priors.as_ref(), game.manifest.duplicates);
+        let suggestion = choose_from_entropies(&entropies, &game.solutions, game.priors.as_ref(), session.strategy);
+        let mut guess = suggestion;
+        loop {
+            match ui.ask_for_pattern(&game, &guess, &entropies) {
+                PatternInput::Pattern(pattern) => {
+                    game.play(guess, pattern);
+                    session.turns.push(Turn {
+                        guess: guess.as_string(&game.translator),
+                        pattern: pattern.as_string(),
+                        suggestion: suggestion.as_string(&game.translator),
+                    });
+                    if let Err(e) = session.save(session_path) {
+                        ui.warn(&game, &format!("could not save the game to {}: {}", session_path.display(), e));
+                    }
+                    break;
+                },
+                // The user played another word, so its pattern is asked
+                // for instead, as long as it can be guessed
+                PatternInput::OtherWord => if let Some(word) = ui.ask_for_word(&game) {
+                    match game.parse_guess(&word) {
+                        Some(word) => guess = word,
+                        None => ui.warn(&game, &format!("{} is not one of the words that can be guessed", word)),
+                    }
+                },
+                PatternInput::Rejected => {
+                    // The game doesn't know the word, so it's saved to be left
+                    // out from now on, and the next best word is used instead
+                    let word = game.manifest.casing().lowercase(&guess.as_string(&game.translator));
+                    if let Err(err) = loader.edit_overlay(&lang, OverlayEdit::RemoveGuess, &word) {
+                        ui.warn(&game, &err.to_string());
+                    }
+                    game.guesses.retain(|w| *w != guess);
+                    game.solutions.retain(|w| *w != guess);
+                    session.rejected.push(word);
+                    break;
+                },
+                PatternInput::Quit => {
+                    // The session was saved after the last turn, so it's kept
+                    // unless the user doesn't want to resume it
+                    let keep = !session.turns.is_empty() && ui.ask_yes_no(&game, "Save the game to resume it later with --resume?");
+                    drop(ui);
+                    if keep {
+                        if let Err(e) = session.save(session_path) {
+                            eprintln!("Warning: could not save the game to {}: {}", session_path.display(), e);
+                        }
+                        println!("Game saved to {}", session_path.display());
+                    } else if !session.turns.is_empty() {
+                        Session::remove(session_path);
+                    }
+                    return Ok(());
+                },
+            }
+        }
     }
 
-    if solutions.is_empty() {
-        println!("Oops, no solutions found... Check that the color patterns are correct and try again.")
-    } else if !guessed_midway {
-        print_in_green(&solutions[0].as_string(&translator));
+    let outcome = if game.is_solved() {
+        Outcome::Solved
+    } else if game.solutions.is_empty() {
+        Outcome::NoSolutions
+    } else if game.solutions.len() > 1 {
+        Outcome::OutOfGuesses
+    } else {
+        Outcome::Answer(game.solutions[0])
+    };
+    ui.finish(&game, &outcome);
+    drop(ui);
+
+    // The game is over, so there is nothing left to resume
+    Session::remove(session_path);
+
+    // The answer is played next if there are guesses left. A game with
+    // a wrong pattern isn't worth keeping.
+    let solved = match outcome {
+        Outcome::Solved => true,
+        Outcome::Answer(answer) if game.turns.len() < max_guesses => {
+            let pattern = Pattern { colors: [Colors::GREEN; MAX_WORD_LEN], len: answer.len };
+            let answer = answer.as_string(&game.translator);
+            session.turns.push(Turn { guess: answer.clone(), pattern: pattern.as_string(), suggestion: answer });
+            true
+        },
+        Outcome::NoSolutions => return Ok(()),
+        _ => false,
+    };
+
+    let game = GameRecord {
+        date: session.date,
+        language: lang,
+        solved,
+        tries: session.turns.len(),
+        max_guesses,
+        guesses: session.turns.iter().map(|turn| turn.guess.clone()).collect(),
+        patterns: session.turns.iter().map(|turn| turn.pattern.clone()).collect(),
+        suggestions: session.turns.into_iter().map(|turn| turn.suggestion).collect(),
+        rejected: session.rejected,
+    };
+    if let Err(e) = append_game(history_path, &game) {
+        eprintln!("Warning: could not save the game to {}: {}", history_path.display(), e);
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::fs::{read_to_string, write};
+
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::dataloader::{ListFiles, LoadError};
+use crate::model::GuessModel;
+
+// The results of a benchmark run, in a format that can be saved to
+// disk and used as the baseline to compare later runs against
+#[derive(Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub language: String,
+    pub opener: String,
+    pub average: f64,
+    pub distribution: Vec<usize>,
+    pub rounds: Vec<RoundReport>,
+    // The model of the guesses needed for some uncertainty, fitted to
+    // the rounds, which older results don't have
+    #[serde(default)]
+    pub model: Option<GuessModel>,
+    // The files that the word lists were read from, which older
+    // results don't have either
+    #[serde(default)]
+    pub files: ListFiles,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RoundReport {
+    pub answer: String,
+    pub tries: usize,
+    pub solved: bool,
+    pub guesses: Vec<String>,
+    pub patterns: Vec<String>,
+}
+
+impl BenchmarkReport {
+    pub fn load(path: &str) -> Result<Self, LoadError> {
+        let content = read_to_string(path)
+            .map_err(|e| LoadError::UnreadableFile { path: path.to_owned(), reason: e.to_string() })?;
+        serde_json::from_str(&content)
+            .map_err(|e| LoadError::BadReport { path: path.to_owned(), reason: e.to_string() })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), LoadError> {
+        let content = serde_json::to_string_pretty(self).unwrap();
+        write(path, content).map_err(|e| LoadError::UnwritableFile { path: path.to_owned(), reason: e.to_string() })?;
+        println!("Benchmark results saved to {}", path);
+        Ok(())
+    }
+
+    // Prints the differences between this report and a baseline, and
+    // returns whether they amount to a regression, which is the case
+    // when the average got worse by more than the given threshold or
+    // when some answer that was solved before is no longer solved
+    pub fn compare(&self, baseline: &Self, max_regression: f64) -> bool {
+        println!();
+        println!("Comparison against baseline ({} -> {}):", baseline.opener, self.opener);
+
+        // The results of different lists can't be compared answer by answer
+        if baseline.files != ListFiles::default() && baseline.files != self.files {
+            println!("Note: the baseline used different word lists");
+            println!("- Guesses: {} -> {}", baseline.files.guesses.join(", "), self.files.guesses.join(", "));
+            println!("- Solutions: {} -> {}", baseline.files.solutions.join(", "), self.files.solutions.join(", "));
+            if baseline.files.used_before != self.files.used_before {
+                let day = |files: &ListFiles| files.used_before.map_or("none".to_owned(), |day| day.to_string());
+                println!("- Left out the answers used before: {} -> {}", day(&baseline.files), day(&self.files));
+            }
+        }
+
+        let delta = self.average - baseline.average;
+        println!("Average: {:.4} -> {:.4} ({:+.4})", baseline.average, self.average, delta);
+
+        // The last row is always the failures, so the rows are matched
+        // by their number of guesses in case the runs allowed different
+        // numbers of them
+        let solved_rows = |distribution: &[usize]| distribution.len() - 1;
+        let (old_rows, new_rows) = (solved_rows(&baseline.distribution), solved_rows(&self.distribution));
+        if old_rows != new_rows {
+            println!("Note: the baseline allowed {} guesses instead of {}", old_rows, new_rows);
+        }
+        println!("Distribution:");
+        let row = |s: String, old: usize, new: usize| println!("- {}: {} -> {} ({:+})", s, old, new, new as i64 - old as i64);
+        for i in 0..old_rows.max(new_rows) {
+            let count = |distribution: &[usize]| if i < solved_rows(distribution) { distribution[i] } else { 0 };
+            row((i+1).to_string(), count(&baseline.distribution), count(&self.distribution));
+        }
+        row("X".to_owned(), baseline.distribution[old_rows], self.distribution[new_rows]);
+
+        // Match every answer with its result in the baseline, answers
+        // that are missing from either of the runs are not compared
+        let old_rounds: FxHashMap<&str, &RoundReport> = baseline.rounds.iter()
+            .map(|r| (r.answer.as_str(), r))
+            .collect();
+
+        let mut better = vec![];
+        let mut worse = vec![];
+        let mut new_failures = vec![];
+
+        for round in &self.rounds {
+            let old = match old_rounds.get(round.answer.as_str()) {
+                Some(old) => old,
+                None => continue,
+            };
+
+            if old.solved && !round.solved {
+                new_failures.push(round.answer.clone());
+            } else if round.tries < old.tries {
+                better.push(format!("{} ({} -> {})", round.answer, old.tries, round.tries));
+            } else if round.tries > old.tries {
+                worse.push(format!("{} ({} -> {})", round.answer, old.tries, round.tries));
+            }
+        }
+
+        print_answers("Improved answers", &better);
+        print_answers("Worsened answers", &worse);
+        print_answers("New failures", &new_failures);
+
+        let regression = delta > max_regression || !new_failures.is_empty();
+        if regression {
+            println!("Regression detected (maximum allowed change in average: {:+.4})", max_regression);
+        }
+
+        regression
+    }
+}
+
+fn print_answers(title: &str, answers: &[String]) {
+    if !answers.is_empty() {
+        println!("{} ({}): {}", title, answers.len(), answers.join(", "));
+    }
+}
@@ -0,0 +1,106 @@
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+use std::io;
+use std::path::{absolute, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dataloader::{DataLoader, Date, ListOverrides, LoadError};
+use crate::dirs::xdg_file;
+use crate::entropy::Strategy;
+
+// An interactive game in progress, which is saved after every turn so
+// that it can be resumed if the terminal is closed or the game is quit
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub language: String,
+    // The day that is being played
+    pub date: Date,
+    pub hard_mode: bool,
+    pub strategy: Strategy,
+    // How the language's lists were loaded, so that they are the same
+    // ones when the game is resumed, even on another day
+    pub keep_used: bool,
+    pub locale: Option<String>,
+    pub lists: ListOverrides,
+    // The guesses that were played, in order, along with their patterns
+    pub turns: Vec<Turn>,
+    // The words that the game didn't accept, which are usually left out
+    // through the overlay, but it can't always be written
+    pub rejected: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Turn {
+    pub guess: String,
+    pub pattern: String,
+    // The word that the solver suggested, which is the guess unless the
+    // user played another word
+    pub suggestion: String,
+}
+
+impl Session {
+    pub fn new(language: &str, date: Date, hard_mode: bool, strategy: Strategy) -> Self {
+        Self {
+            language: language.to_lowercase(),
+            date,
+            hard_mode,
+            strategy,
+            keep_used: false,
+            locale: None,
+            lists: ListOverrides::default(),
+            turns: vec![],
+            rejected: vec![],
+        }
+    }
+
+    // Remembers how the lists are loaded. The files that replace them
+    // are saved with their full paths, in case the game is resumed from
+    // another folder.
+    pub fn with_lists(self, keep_used: bool, locale: Option<String>, lists: ListOverrides) -> Self {
+        let full_path = |path: PathBuf| absolute(&path).unwrap_or(path);
+        let lists = ListOverrides { guesses: lists.guesses.map(full_path), solutions: lists.solutions.map(full_path), ..lists };
+        Self { keep_used, locale, lists, ..self }
+    }
+
+    // The loader for the lists that the game was started with
+    pub fn loader(&self, data_dir: Option<&Path>) -> DataLoader {
+        DataLoader::new(data_dir, self.locale.clone(), (!self.keep_used).then_some(self.date), self.lists.clone())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, LoadError> {
+        let path_name = path.display().to_string();
+        let content = read_to_string(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => LoadError::NoSession { path: path_name.clone() },
+            _ => LoadError::UnreadableFile { path: path_name.clone(), reason: e.to_string() },
+        })?;
+        serde_json::from_str(&content).map_err(|e| LoadError::BadSession { path: path_name, reason: e.to_string() })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(folder) = path.parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            create_dir_all(folder)?;
+        }
+        write(path, serde_json::to_string_pretty(self).unwrap())
+    }
+
+    // Removes a saved session, which is no longer needed once the game
+    // is over. It's fine if it was never saved.
+    pub fn remove(path: &Path) {
+        if let Err(e) = remove_file(path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("Warning: could not remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+// Where the session is saved by default
+pub fn default_session_path() -> PathBuf {
+    state_path("session.json")
+}
+
+// The path to one of the files that Eldrow keeps between runs, which are
+// in the eldrow folder inside the XDG state home (~/.local/state)
+pub fn state_path(file: &str) -> PathBuf {
+    xdg_file("XDG_STATE_HOME", ".local/state", file)
+}
@@ -0,0 +1,56 @@
+use crate::benchmark::print_distribution;
+use crate::history::GameRecord;
+
+// Prints the statistics of the finished games for every language, in
+// the same format as the benchmark results, along with the streaks of
+// solved games and how often the user played the suggested words
+pub fn print_stats(games: &[GameRecord]) {
+    if games.is_empty() {
+        println!("No games were finished yet");
+        return;
+    }
+
+    let mut langs: Vec<&str> = games.iter().map(|game| game.language.as_str()).collect();
+    langs.sort_unstable();
+    langs.dedup();
+
+    for (i, lang) in langs.iter().enumerate() {
+        let games: Vec<&GameRecord> = games.iter().filter(|game| game.language == *lang).collect();
+        if i > 0 {
+            println!();
+        }
+        println!("Statistics for {} ({} games):", lang, games.len());
+
+        // The maximum number of guesses only changes if the language does
+        let max_guesses = games.iter().map(|game| game.max_guesses).max().unwrap();
+        let mut counts = vec![0; max_guesses + 1];
+        for game in &games {
+            let tries = if game.solved { game.tries } else { max_guesses + 1 };
+            counts[tries.min(max_guesses + 1) - 1] += 1;
+        }
+        print_distribution(&counts);
+
+        // Streaks are counted in the order that the games were finished
+        let (mut streak, mut longest) = (0, 0);
+        for game in &games {
+            streak = if game.solved { streak + 1 } else { 0 };
+            longest = longest.max(streak);
+        }
+        println!("Current streak: {}, longest streak: {}", streak, longest);
+
+        // Rejected suggestions don't count, since the game didn't let
+        // the user play them
+        let followed = |game: &GameRecord| game.guesses.iter().zip(&game.suggestions).filter(|(guess, suggestion)| guess == suggestion).count();
+        let n_guesses: usize = games.iter().map(|game| game.guesses.len()).sum();
+        let n_followed: usize = games.iter().map(|game| followed(game)).sum();
+        let all_followed = games.iter().filter(|game| followed(game) == game.guesses.len()).count();
+        let n_rejected: usize = games.iter().map(|game| game.rejected.len()).sum();
+        println!("Guesses that matched the suggestions: {} of {} ({:.2}%)", n_followed, n_guesses, percent(n_followed, n_guesses));
+        println!("Games that followed every suggestion: {} of {} ({:.2}%)", all_followed, games.len(), percent(all_followed, games.len()));
+        println!("Suggestions rejected by the game: {}", n_rejected);
+    }
+}
+
+fn percent(count: usize, total: usize) -> f32 {
+    count as f32 / total.max(1) as f32 * 100.0
+}